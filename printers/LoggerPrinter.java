@@ -0,0 +1,122 @@
+import com.google.gson.Gson;
+import com.google.gson.JsonArray;
+import com.google.gson.JsonObject;
+import java.io.FileWriter;
+import java.io.IOException;
+import java.lang.reflect.Field;
+import java.lang.reflect.InvocationTargetException;
+import java.lang.reflect.Method;
+import java.lang.reflect.Modifier;
+import java.util.Map;
+import java.util.TreeMap;
+
+/**
+ * Writes all loggers registered in Carpet's `LoggerRegistry` to `loggers.json`.
+ *
+ * This only uses reflection to access Carpet, so it compiles with every printer version.
+ */
+public class LoggerPrinter {
+    public static void print() {
+        System.out.println("[data-extractor] printing loggers");
+        JsonArray loggers = new JsonArray();
+        try {
+            initLoggers();
+            Class<?> hudLogger = Class.forName("carpet.logging.HUDLogger");
+            for (Map.Entry<String, Object> entry : registry().entrySet()) {
+                Object logger = entry.getValue();
+                JsonObject obj = new JsonObject();
+                obj.addProperty("name", entry.getKey());
+                Object defaultOption = get(logger, "getDefault", "default_option");
+                if (defaultOption != null) obj.addProperty("default", defaultOption.toString());
+                JsonArray options = new JsonArray();
+                Object optionsArray = get(logger, "getOptions", "options");
+                if (optionsArray instanceof String[]) {
+                    for (String option : (String[]) optionsArray) options.add(option);
+                }
+                obj.add("options", options);
+                obj.addProperty("hud", hudLogger.isInstance(logger));
+                obj.addProperty("carpet", isFromCarpet(logger));
+                loggers.add(obj);
+            }
+        } catch (Exception e) {
+            System.err.println("Warning: could not read the logger registry: " + e);
+            return;
+        }
+
+        try (FileWriter writer = new FileWriter("loggers.json")) {
+            writer.write(new Gson().toJson(loggers));
+        } catch (IOException e) {
+            throw new RuntimeException(e);
+        }
+    }
+
+    /**
+     * Registers the loggers of Carpet and all extensions, which Carpet only does once a server has
+     * loaded, so never while printing.
+     *
+     * `initLoggers()` clears the registry and calls `registerLoggers()` and, in versions with
+     * extensions, `CarpetServer.registerExtensionLoggers()`.
+     */
+    private static void initLoggers() throws Exception {
+        Method initLoggers = Class.forName("carpet.logging.LoggerRegistry").getDeclaredMethod("initLoggers");
+        initLoggers.setAccessible(true);
+        try {
+            initLoggers.invoke(null);
+        } catch (InvocationTargetException e) {
+            // an extension failing to register its loggers still leaves the others
+            System.err.println("Warning: could not register all loggers: " + e.getCause());
+        }
+    }
+
+    /** Returns the loggers by name, from the static map in `LoggerRegistry`. */
+    @SuppressWarnings("unchecked")
+    private static Map<String, Object> registry() throws Exception {
+        for (Field field : Class.forName("carpet.logging.LoggerRegistry").getDeclaredFields()) {
+            if (!Modifier.isStatic(field.getModifiers()) || !Map.class.isAssignableFrom(field.getType())) continue;
+            field.setAccessible(true);
+            return new TreeMap<>((Map<String, Object>) field.get(null));
+        }
+        throw new NoSuchFieldException("LoggerRegistry has no logger map");
+    }
+
+    /** Calls the getter, or reads the field in Carpet versions without the getter. */
+    private static Object get(Object logger, String getter, String fieldName) throws Exception {
+        try {
+            Method method = logger.getClass().getMethod(getter);
+            return method.invoke(logger);
+        } catch (NoSuchMethodException e) {
+            Field field = findField(logger.getClass(), fieldName);
+            return field != null ? field.get(logger) : null;
+        }
+    }
+
+    /**
+     * Returns whether Carpet registered the logger itself, which is the case if its accelerator
+     * field, the static boolean that is set while the logger has subscribers, is declared by
+     * Carpet.
+     */
+    private static boolean isFromCarpet(Object logger) throws Exception {
+        for (Class<?> clazz = logger.getClass(); clazz != null; clazz = clazz.getSuperclass()) {
+            for (Field field : clazz.getDeclaredFields()) {
+                if (field.getType() != Field.class) continue;
+                field.setAccessible(true);
+                Field accelerator = (Field) field.get(logger);
+                return accelerator != null && accelerator.getDeclaringClass().getName().startsWith("carpet.");
+            }
+        }
+        return false;
+    }
+
+    private static Field findField(Class<?> clazz, String name) {
+        for (; clazz != null; clazz = clazz.getSuperclass()) {
+            try {
+                Field field = clazz.getDeclaredField(name);
+                field.setAccessible(true);
+                return field;
+            } catch (NoSuchFieldException e) {
+                // look in the superclass
+            }
+        }
+        return null;
+    }
+}
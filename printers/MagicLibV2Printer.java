@@ -21,18 +21,44 @@ public class Printer {
     private static final Gson gson = new Gson();
 
     public static void print() {
+        System.out.println("[data-extractor] printing rules");
+        WrappedSettingManager[] settingsManagers = new WrappedSettingManager[] {${SETTINGS_MANAGERS}};
         List<String> ruleNames = new ArrayList<>();
-        for (Class<?> clazz : new Class<?>[] {SETTINGS_CLASSES}) {
-            for (Field field : clazz.getDeclaredFields()) {
-                if (field.getAnnotation(RULE.class) == null) continue;
-                ruleNames.add(field.getName());
+        if (${DISCOVER_FROM_MANAGERS}) {
+            // every registered rule, except for the ones of Carpet itself
+            List<String> carpetRuleNames = new ArrayList<>();
+            for (Field field : carpet.CarpetSettings.class.getDeclaredFields()) {
+                carpetRuleNames.add(field.getName());
+            }
+            for (WrappedSettingManager settingsManager : settingsManagers) {
+                for (CarpetRule<?> rule : settingsManager.getCarpetRules()) {
+                    if (carpetRuleNames.contains(rule.name()) || ruleNames.contains(rule.name())) continue;
+                    ruleNames.add(rule.name());
+                }
+            }
+        } else {
+            for (Class<?> clazz : new Class<?>[] {${SETTINGS_CLASSES}}) {
+                for (Field field : clazz.getDeclaredFields()) {
+                    if (field.getAnnotation(${RULE_ANNOTATION}.class) == null) continue;
+                    ruleNames.add(field.getName());
+                }
             }
         }
 
         JsonArray rules = new JsonArray();
-        WrappedSettingManager settingsManager = mixin.PrivateSettingsManagerAccessor.getSettingsManager();
+        RuleTranslations translations = RuleTranslations.load();
         for (String ruleName : ruleNames) {
-            CarpetRule<?> rule = settingsManager.getCarpetRule(ruleName);
+            CarpetRule<?> rule = null;
+            WrappedSettingManager settingsManager = null;
+            JsonArray configFiles = new JsonArray();
+            for (WrappedSettingManager manager : settingsManagers) {
+                CarpetRule<?> hasRule = manager.getCarpetRule(ruleName);
+                if (hasRule != null) {
+                    rule = hasRule;
+                    settingsManager = manager;
+                    configFiles.add(manager.getIdentifier());
+                }
+            }
             if (rule == null) {
                 System.err.println("Warning: rule '" + ruleName + "' could not be found in any SettingsManager");
                 continue;
@@ -63,12 +89,13 @@ public class Printer {
                         .toList());
             }
             obj.add("validators", gson.toJsonTree(validators));
-            JsonArray configFiles = new JsonArray();
-            configFiles.add(settingsManager.getIdentifier());
             obj.add("config_files", configFiles);
+            obj.add("translations", translations.forRule(settingsManager.getIdentifier() + ".rule.", rule.name()));
             rules.add(obj);
         }
 
+        // before the rules, whose file marks the end of the extraction
+        LoggerPrinter.print();
         try (FileWriter writer = new FileWriter("rules.json")) {
             writer.write(gson.toJson(rules));
         } catch (IOException e) {
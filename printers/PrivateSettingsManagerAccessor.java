@@ -0,0 +1,12 @@
+package mixin;
+
+import org.spongepowered.asm.mixin.Mixin;
+import org.spongepowered.asm.mixin.gen.Accessor;
+
+@Mixin(${TARGET_CLASS}.class)
+public interface ${ACCESSOR} {
+    @Accessor(value = "${FIELD_NAME}", remap = false)
+    static ${SETTINGS_MANAGER_CLASS} getSettingsManager() {
+        throw new AssertionError();
+    }
+}
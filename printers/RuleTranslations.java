@@ -0,0 +1,131 @@
+import com.google.gson.Gson;
+import com.google.gson.JsonArray;
+import com.google.gson.JsonObject;
+import com.google.gson.reflect.TypeToken;
+import java.io.IOException;
+import java.io.Reader;
+import java.lang.reflect.Method;
+import java.nio.charset.StandardCharsets;
+import java.nio.file.Files;
+import java.nio.file.Path;
+import java.util.ArrayList;
+import java.util.HashMap;
+import java.util.List;
+import java.util.Map;
+import java.util.TreeMap;
+import java.util.regex.Matcher;
+import java.util.regex.Pattern;
+import java.util.stream.Collectors;
+import java.util.stream.Stream;
+import net.fabricmc.loader.api.FabricLoader;
+import net.fabricmc.loader.api.ModContainer;
+
+/**
+ * The rule translations of every language shipped by Carpet and the loaded extensions, except for
+ * English, which the printers already use.
+ *
+ * This only uses reflection to access Carpet, so it compiles with every printer version.
+ */
+public class RuleTranslations {
+    private static final Pattern LANG_FILE = Pattern.compile("^([a-z]{2,3}_[a-z]{2,4})\\.(json|ya?ml)$");
+
+    private final Map<String, Map<String, String>> byLanguage = new TreeMap<>();
+
+    public static RuleTranslations load() {
+        RuleTranslations translations = new RuleTranslations();
+        Map<String, List<Path>> jsonFiles = new TreeMap<>();
+        for (ModContainer mod : FabricLoader.getInstance().getAllMods()) {
+            for (Path root : mod.getRootPaths()) {
+                Path assets = root.resolve("assets");
+                if (!Files.isDirectory(assets)) continue;
+                try (Stream<Path> files = Files.walk(assets, 3)) {
+                    for (Path file : files.collect(Collectors.toList())) {
+                        if (file.getParent() == null || !file.getParent().getFileName().toString().equals("lang")) continue;
+                        Matcher matcher = LANG_FILE.matcher(file.getFileName().toString());
+                        if (!matcher.matches()) continue;
+                        List<Path> paths = jsonFiles.computeIfAbsent(matcher.group(1), key -> new ArrayList<>());
+                        // YAML files are only read by the extensions themselves
+                        if (matcher.group(2).equals("json")) paths.add(file);
+                    }
+                } catch (IOException e) {
+                    System.err.println("Warning: could not read the language files of '" + mod.getMetadata().getId() + "': " + e);
+                }
+            }
+        }
+
+        Gson gson = new Gson();
+        for (Map.Entry<String, List<Path>> entry : jsonFiles.entrySet()) {
+            String language = entry.getKey();
+            if (language.equals("en_us")) continue;
+            Map<String, String> map = new HashMap<>();
+            for (Path file : entry.getValue()) {
+                try (Reader reader = Files.newBufferedReader(file, StandardCharsets.UTF_8)) {
+                    Map<String, String> fileMap = gson.fromJson(reader, new TypeToken<Map<String, String>>() {}.getType());
+                    if (fileMap != null) map.putAll(fileMap);
+                } catch (Exception e) {
+                    System.err.println("Warning: could not read language file '" + file + "': " + e);
+                }
+            }
+            map.putAll(extensionTranslations(language));
+            if (!map.isEmpty()) translations.byLanguage.put(language, map);
+        }
+        return translations;
+    }
+
+    /** Returns what all Carpet extensions provide for the language with `canHasTranslations`. */
+    private static Map<String, String> extensionTranslations(String language) {
+        Map<String, String> map = new HashMap<>();
+        Iterable<?> extensions;
+        try {
+            extensions = (Iterable<?>) Class.forName("carpet.CarpetServer").getField("extensions").get(null);
+        } catch (Exception e) {
+            System.err.println("Warning: could not get the Carpet extensions: " + e);
+            return map;
+        }
+        for (Object extension : extensions) {
+            Object result;
+            try {
+                Method method = extension.getClass().getMethod("canHasTranslations", String.class);
+                result = method.invoke(extension, language);
+            } catch (NoSuchMethodException e) {
+                // Carpet versions without translations
+                return map;
+            } catch (Exception e) {
+                System.err.println("Warning: could not get the '" + language + "' translations of " + extension.getClass().getName() + ": " + e);
+                continue;
+            }
+            if (!(result instanceof Map)) continue;
+            for (Map.Entry<?, ?> entry : ((Map<?, ?>) result).entrySet()) {
+                map.put(String.valueOf(entry.getKey()), String.valueOf(entry.getValue()));
+            }
+        }
+        return map;
+    }
+
+    /**
+     * Returns the description, extras, and additional info of the rule by language, for all
+     * languages that translate its description.
+     *
+     * @param prefix the prefix of the translation keys before the rule name, like `carpet.rule.`
+     */
+    public JsonObject forRule(String prefix, String name) {
+        JsonObject result = new JsonObject();
+        String key = prefix + name;
+        for (Map.Entry<String, Map<String, String>> entry : byLanguage.entrySet()) {
+            Map<String, String> map = entry.getValue();
+            String description = map.get(key + ".desc");
+            if (description == null) continue;
+            JsonObject obj = new JsonObject();
+            obj.addProperty("description", description);
+            JsonArray extras = new JsonArray();
+            for (int i = 0; map.containsKey(key + ".extra." + i); i++) {
+                extras.add(map.get(key + ".extra." + i));
+            }
+            obj.add("extras", extras);
+            String additional = map.get(key + ".additional");
+            if (additional != null) obj.addProperty("additional", additional);
+            result.add(entry.getKey(), obj);
+        }
+        return result;
+    }
+}
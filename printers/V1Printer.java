@@ -16,17 +16,33 @@ import org.apache.commons.lang3.ClassUtils;
 
 public class Printer {
     public static void print() {
+        System.out.println("[data-extractor] printing rules");
+        SettingsManager[] settingsManagers = new SettingsManager[] {${SETTINGS_MANAGERS}};
         List<String> ruleNames = new ArrayList<>();
-        for (Class<?> clazz : new Class<?>[] {SETTINGS_CLASSES}) {
-            for (Field field : clazz.getDeclaredFields()) {
-                if (field.getAnnotation(RULE.class) == null) continue;
-                ruleNames.add(field.getName());
+        if (${DISCOVER_FROM_MANAGERS}) {
+            // every registered rule, except for the ones of Carpet itself
+            List<String> carpetRuleNames = new ArrayList<>();
+            for (Field field : carpet.CarpetSettings.class.getDeclaredFields()) {
+                carpetRuleNames.add(field.getName());
+            }
+            for (SettingsManager settingsManager : settingsManagers) {
+                for (ParsedRule<?> rule : settingsManager.getRules()) {
+                    if (carpetRuleNames.contains(rule.name) || ruleNames.contains(rule.name)) continue;
+                    ruleNames.add(rule.name);
+                }
+            }
+        } else {
+            for (Class<?> clazz : new Class<?>[] {${SETTINGS_CLASSES}}) {
+                for (Field field : clazz.getDeclaredFields()) {
+                    if (field.getAnnotation(${RULE_ANNOTATION}.class) == null) continue;
+                    ruleNames.add(field.getName());
+                }
             }
         }
 
         Gson gson = new Gson();
         JsonArray rules = new JsonArray();
-        SettingsManager[] settingsManagers = new SettingsManager[] {SETTINGS_MANAGERS};
+        RuleTranslations translations = RuleTranslations.load();
         for (String ruleName : ruleNames) {
             ParsedRule<?> rule = null;
             JsonArray configFiles = new JsonArray();
@@ -63,9 +79,12 @@ public class Printer {
                             .filter(Objects::nonNull)
                             .collect(Collectors.toList())));
             obj.add("config_files", configFiles);
+            obj.add("translations", translations.forRule("rule.", rule.name));
             rules.add(obj);
         }
 
+        // before the rules, whose file marks the end of the extraction
+        LoggerPrinter.print();
         try (FileWriter writer = new FileWriter("rules.json")) {
             writer.write(gson.toJson(rules));
         } catch (IOException e) {
@@ -0,0 +1,99 @@
+use std::{env, fmt::Display, str::FromStr};
+
+use anyhow::{bail, Context, Result};
+
+/// Whether a flag is a switch like `--quiet` or takes a value like `--timeout=60`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Switch,
+    Value,
+}
+
+/// All flags accepted on the command line, without the leading `--`.
+const KNOWN_FLAGS: &[(&str, Kind)] = &[
+    ("color", Kind::Value),
+    ("dry-run", Kind::Switch),
+    ("gradle-retries", Kind::Value),
+    ("keep-going", Kind::Switch),
+    ("keep-logs", Kind::Value),
+    ("log-json", Kind::Value),
+    ("log-level", Kind::Value),
+    ("quiet", Kind::Switch),
+    ("report", Kind::Value),
+    ("report-dir", Kind::Value),
+    ("sandbox", Kind::Switch),
+    ("sandbox-cpus", Kind::Value),
+    ("sandbox-memory", Kind::Value),
+    ("sandbox-network-only", Kind::Switch),
+    ("stale", Kind::Value),
+    ("stale-only", Kind::Switch),
+    ("timeout", Kind::Value),
+];
+
+/// The parsed command line: an optional command followed by any number of `--flag` or
+/// `--flag=value` arguments.
+pub struct Args {
+    pub command: Option<String>,
+    flags: Vec<(String, Option<String>)>,
+}
+
+impl Args {
+    pub fn parse() -> Result<Self> {
+        let mut command = None;
+        let mut flags = vec![];
+        for arg in env::args().skip(1) {
+            if let Some(flag) = arg.strip_prefix("--") {
+                let (name, value) = match flag.split_once('=') {
+                    Some((name, value)) => (name, Some(value.to_owned())),
+                    None => (flag, None),
+                };
+                let Some((_, kind)) = KNOWN_FLAGS.iter().find(|(known, _)| *known == name) else {
+                    bail!("unknown flag `--{name}`");
+                };
+                match (kind, &value) {
+                    (Kind::Switch, Some(_)) => bail!("flag `--{name}` does not take a value"),
+                    (Kind::Value, None) => {
+                        bail!("flag `--{name}` requires a value, like `--{name}=...`")
+                    }
+                    _ => {}
+                }
+                flags.push((name.to_owned(), value));
+            } else if command.is_none() {
+                command = Some(arg);
+            } else {
+                bail!("unexpected argument `{arg}`");
+            }
+        }
+        Ok(Self { command, flags })
+    }
+
+    /// Returns whether the given flag was passed.
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|(flag, _)| flag == name)
+    }
+
+    /// Returns the value of the last occurrence of the given flag, if any.
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.flags
+            .iter()
+            .rev()
+            .find(|(flag, _)| flag == name)
+            .and_then(|(_, value)| value.as_deref())
+    }
+
+    /// Parses the value of the last occurrence of the given flag, if any.
+    pub fn parse_value<T>(&self, name: &str) -> Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.value(name)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|err| anyhow::anyhow!("{err}"))
+                    .with_context(|| format!("invalid value `{value}` for `--{name}`"))
+            })
+            .transpose()
+    }
+}
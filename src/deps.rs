@@ -0,0 +1,437 @@
+//! Automatic resolution of the Carpet and library dependencies a mod jar declares in its
+//! `fabric.mod.json` or `quilt.mod.json`.
+
+use std::{
+    cmp::Ordering,
+    collections::BTreeSet,
+    io::{Cursor, Read},
+};
+
+use anyhow::{bail, Context, Result};
+use itertools::Itertools;
+use lazy_regex::regex_captures;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use zip::{result::ZipError, ZipArchive};
+
+use crate::{
+    log, retry,
+    schema::{Loader, MinecraftVersion},
+};
+
+/// Mod ids which are resolved from Modrinth, with their Modrinth slug.
+///
+/// Other ids are ignored. Fabric API in particular is always part of the template mod, and the
+/// loader, Minecraft, and Java are given by the template anyway.
+const KNOWN_MODS: &[(&str, &str)] = &[("carpet", "carpet"), ("magiclib", "magiclib")];
+
+/// The relevant parts of a Modrinth version.
+#[derive(Deserialize)]
+struct ModrinthVersion {
+    version_number: String,
+    version_type: String,
+    files: Vec<ModrinthFile>,
+}
+
+#[derive(Deserialize)]
+struct ModrinthFile {
+    url: String,
+    primary: bool,
+}
+
+/// A dependency the mod jar declares, with the version predicates of which any must match.
+#[derive(Debug)]
+pub struct Declared {
+    pub id: String,
+    pub slug: &'static str,
+    pub predicates: Vec<String>,
+}
+
+/// The parts of a `fabric.mod.json` or `quilt.mod.json` that are needed here.
+struct ModJson {
+    id: Option<String>,
+    /// the required mod ids with the version predicates of which any must match
+    depends: Vec<(String, Vec<String>)>,
+    /// the paths of the nested jars
+    jars: Vec<String>,
+}
+
+/// Returns the known dependencies declared in the jar's `fabric.mod.json` or `quilt.mod.json`,
+/// except for those that are bundled inside the jar.
+pub fn declared(jar: &[u8]) -> Result<Vec<Declared>> {
+    let mut archive = ZipArchive::new(Cursor::new(jar))?;
+    let Some(conf) = read_mod_json(&mut archive)? else {
+        log::debug!("the mod jar contains no fabric.mod.json or quilt.mod.json");
+        return Ok(vec![]);
+    };
+
+    let bundled = bundled_ids(&mut archive, &conf)?;
+    let mut declared = vec![];
+    for (id, predicates) in conf.depends {
+        let Some((_, slug)) = KNOWN_MODS.iter().find(|(known, _)| *known == id) else {
+            log::debug!("ignoring dependency on `{id}`");
+            continue;
+        };
+        if bundled.contains(&id) {
+            log::debug!("`{id}` is bundled with the mod");
+            continue;
+        }
+        declared.push(Declared {
+            id,
+            slug,
+            predicates,
+        });
+    }
+    Ok(declared)
+}
+
+/// Reads the `fabric.mod.json`, or the `quilt.mod.json` if there is none.
+fn read_mod_json(archive: &mut ZipArchive<Cursor<&[u8]>>) -> Result<Option<ModJson>> {
+    if let Some(conf) = read_json(archive, "fabric.mod.json")? {
+        let depends = match conf.get("depends") {
+            Some(Value::Object(depends)) => depends
+                .iter()
+                .map(|(id, predicates)| Ok((id.clone(), fabric_predicates(id, predicates)?)))
+                .collect::<Result<_>>()?,
+            _ => vec![],
+        };
+        let jars = conf
+            .get("jars")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|jar| Some(jar.get("file")?.as_str()?.to_owned()))
+            .collect();
+        return Ok(Some(ModJson {
+            id: conf.get("id").and_then(Value::as_str).map(str::to_owned),
+            depends,
+            jars,
+        }));
+    }
+
+    let Some(conf) = read_json(archive, "quilt.mod.json")? else {
+        return Ok(None);
+    };
+    let Some(loader) = conf.get("quilt_loader") else {
+        bail!("invalid quilt.mod.json: missing `quilt_loader`");
+    };
+    let mut depends = vec![];
+    for dependency in loader
+        .get("depends")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        match dependency {
+            Value::String(id) => depends.push((id.clone(), vec![])),
+            Value::Object(dependency) => {
+                let Some(id) = dependency.get("id").and_then(Value::as_str) else {
+                    bail!("invalid quilt.mod.json: dependency without id: {dependency:?}");
+                };
+                if dependency.get("optional").and_then(Value::as_bool) == Some(true) {
+                    log::debug!("ignoring optional dependency on `{id}`");
+                    continue;
+                }
+                let predicates = match dependency.get("versions") {
+                    Some(versions) => quilt_predicates(id, versions)?,
+                    None => vec![],
+                };
+                depends.push((id.to_owned(), predicates));
+            }
+            // any one of the alternatives is enough, so none of them is required
+            _ => log::debug!("ignoring alternative dependencies {dependency}"),
+        }
+    }
+    let jars = loader
+        .get("jars")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|jar| Some(jar.as_str()?.to_owned()))
+        .collect();
+    Ok(Some(ModJson {
+        id: loader.get("id").and_then(Value::as_str).map(str::to_owned),
+        depends,
+        jars,
+    }))
+}
+
+fn read_json(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    name: &str,
+) -> Result<Option<Map<String, Value>>> {
+    let mut file = match archive.by_name(name) {
+        Ok(file) => file,
+        Err(ZipError::FileNotFound) => return Ok(None),
+        Err(err) => return Err(err).with_context(|| format!("could not read {name}")),
+    };
+    let mut str = String::new();
+    file.read_to_string(&mut str)?;
+    // some mods have raw line breaks in their descriptions, which are not valid JSON
+    serde_json::from_str(&str.replace(['\n', '\r'], " "))
+        .map(Some)
+        .with_context(|| format!("invalid {name}"))
+}
+
+/// Returns the predicates of a `fabric.mod.json` dependency, a string or an array of alternatives.
+fn fabric_predicates(id: &str, predicates: &Value) -> Result<Vec<String>> {
+    Ok(match predicates {
+        Value::String(predicate) => vec![predicate.clone()],
+        Value::Array(predicates) => predicates
+            .iter()
+            .filter_map(|predicate| predicate.as_str().map(str::to_owned))
+            .collect(),
+        _ => bail!("invalid version predicate for dependency `{id}`: {predicates}"),
+    })
+}
+
+/// Returns the predicates of a `quilt.mod.json` dependency, which is like in `fabric.mod.json`,
+/// or an object with either `any` alternatives or `all` predicates that must match.
+fn quilt_predicates(id: &str, versions: &Value) -> Result<Vec<String>> {
+    if let Some(all) = versions.get("all").and_then(Value::as_array) {
+        // space-separated predicates must all match
+        let all = all.iter().filter_map(Value::as_str).join(" ");
+        return Ok(vec![all]);
+    }
+    match versions.get("any") {
+        Some(any) => fabric_predicates(id, any),
+        None => fabric_predicates(id, versions),
+    }
+}
+
+/// Returns the mod ids of all jars nested in the jar, recursively.
+fn bundled_ids(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    conf: &ModJson,
+) -> Result<BTreeSet<String>> {
+    let mut ids = BTreeSet::new();
+    for path in &conf.jars {
+        let mut bytes = vec![];
+        match archive.by_name(path) {
+            Ok(mut file) => file.read_to_end(&mut bytes)?,
+            Err(_) => {
+                log::warning!("nested jar '{path}' is missing from the mod jar");
+                continue;
+            }
+        };
+        let mut nested = ZipArchive::new(Cursor::new(&bytes[..]))
+            .with_context(|| format!("invalid nested jar '{path}'"))?;
+        let Ok(Some(nested_conf)) = read_mod_json(&mut nested) else {
+            continue;
+        };
+        ids.extend(nested_conf.id.clone());
+        ids.extend(bundled_ids(&mut nested, &nested_conf)?);
+    }
+    Ok(ids)
+}
+
+/// Returns the Modrinth or CurseForge slug of a dependency in Gradle notation, which is used to
+/// let explicit dependencies override resolved ones.
+pub fn dependency_slug(dependency: &str) -> Option<&str> {
+    if let Some((_, slug)) = regex_captures!(r"^maven\.modrinth:([^:]+):", dependency) {
+        return Some(slug);
+    }
+    regex_captures!(r"^curse\.maven:(.+)-\d+:", dependency).map(|(_, slug)| slug)
+}
+
+/// Resolves the newest Modrinth version of the dependency that matches its predicates and
+/// supports the Minecraft version, and returns it in Gradle notation.
+pub async fn resolve(
+    Declared {
+        id,
+        slug,
+        predicates,
+    }: &Declared,
+    minecraft_version: MinecraftVersion,
+    loader: Loader,
+) -> Result<String> {
+    let loaders = match loader {
+        Loader::Fabric => r#"["fabric"]"#,
+        // Quilt loads Fabric mods as well
+        Loader::Quilt => r#"["fabric","quilt"]"#,
+    };
+    let url = format!(
+        "https://api.modrinth.com/v2/project/{slug}/version?game_versions={}&loaders={}",
+        encode_query(&format!(r#"["{minecraft_version}"]"#)),
+        encode_query(loaders),
+    );
+    let (status, bytes) = retry::get(&url).await?;
+    if !status.is_success() {
+        bail!("could not get versions of `{slug}`: Modrinth responded with status code {status}");
+    }
+    // Modrinth lists the newest versions first
+    let versions = serde_json::from_slice::<Vec<ModrinthVersion>>(&bytes)?;
+    let matching = versions
+        .iter()
+        .filter(|version| {
+            // an empty list allows any version
+            predicates.is_empty()
+                || predicates
+                    .iter()
+                    .any(|predicate| matches(predicate, &version.version_number))
+        })
+        .collect::<Vec<_>>();
+    let version = matching
+        .iter()
+        .find(|version| version.version_type == "release")
+        .or(matching.first())
+        .with_context(|| {
+            format!(
+                "no version of `{slug}` for Minecraft {minecraft_version} matches `{id}` {}",
+                predicates.join(" or ")
+            )
+        })?;
+    Ok(format!("maven.modrinth:{slug}:{}", version.version_number))
+}
+
+/// Returns the URL of the primary file of a Modrinth version.
+pub async fn modrinth_file_url(slug: &str, version: &str) -> Result<String> {
+    let url = format!("https://api.modrinth.com/v2/project/{slug}/version/{version}");
+    let (status, bytes) = retry::get(&url).await?;
+    if !status.is_success() {
+        bail!("could not get version `{version}` of `{slug}`: Modrinth responded with status code {status}");
+    }
+    let version = serde_json::from_slice::<ModrinthVersion>(&bytes)?;
+    version
+        .files
+        .iter()
+        .find(|file| file.primary)
+        .or(version.files.first())
+        .map(|file| file.url.clone())
+        .context("the Modrinth version has no files")
+}
+
+fn encode_query(str: &str) -> String {
+    str.replace('[', "%5B")
+        .replace(']', "%5D")
+        .replace('"', "%22")
+        .replace(',', "%2C")
+}
+
+/// Returns whether the version matches the Fabric version predicate.
+///
+/// This supports the common subset of predicates: space-separated conjunctions of `*`, exact
+/// versions with optional `x` wildcards, and the `=`, `>`, `>=`, `<`, `<=`, `~`, and `^`
+/// operators. Versions that cannot be compared are assumed to match.
+fn matches(predicate: &str, version: &str) -> bool {
+    let Some(version) = parse_version(version) else {
+        return true;
+    };
+    predicate.split_whitespace().all(|part| {
+        let (op, bound) = match part.find(|c: char| c.is_ascii_alphanumeric() || c == '*') {
+            Some(index) => part.split_at(index),
+            None => return true,
+        };
+        if matches!(bound, "*" | "x" | "X") {
+            return true;
+        }
+        let wildcard = bound
+            .split('.')
+            .position(|part| matches!(part, "x" | "X" | "*"));
+        let Some(bound) = parse_version(bound) else {
+            return true;
+        };
+        let ordering = compare(&version, &bound);
+        match op {
+            ">=" => ordering.is_ge(),
+            ">" => ordering.is_gt(),
+            "<=" => ordering.is_le(),
+            "<" => ordering.is_lt(),
+            "~" => {
+                let len = bound.len().min(2);
+                ordering.is_ge()
+                    && compare(&version[..version.len().min(len)], &bound[..len]).is_le()
+            }
+            "^" => ordering.is_ge() && version.first() == bound.first(),
+            "" | "=" => match wildcard {
+                Some(len) => version.iter().take(len).eq(bound.iter().take(len)),
+                None => ordering.is_eq(),
+            },
+            _ => true,
+        }
+    })
+}
+
+/// Returns the numeric components at the start of the version, ignoring suffixes like
+/// `-beta.1` or `+build.5`.
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    let (_, numbers) = regex_captures!(r"^v?(\d+(?:\.\d+)*)", version)?;
+    numbers.split('.').map(|part| part.parse().ok()).collect()
+}
+
+fn compare(a: &[u64], b: &[u64]) -> Ordering {
+    (0..a.len().max(b.len()))
+        .map(|index| a.get(index).unwrap_or(&0).cmp(b.get(index).unwrap_or(&0)))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_version() {
+        assert_eq!(
+            super::parse_version("1.4.101+v230319"),
+            Some(vec![1, 4, 101])
+        );
+        assert_eq!(
+            super::parse_version("0.7.398+fe2125a-stable"),
+            Some(vec![0, 7, 398])
+        );
+        assert_eq!(super::parse_version("v1.2-beta.1"), Some(vec![1, 2]));
+        assert_eq!(super::parse_version("1.4.x"), Some(vec![1, 4]));
+        assert_eq!(super::parse_version("beta-3"), None);
+    }
+
+    #[test]
+    fn compare() {
+        assert_eq!(super::compare(&[1, 4], &[1, 4, 0]), Ordering::Equal);
+        assert_eq!(super::compare(&[1, 4, 101], &[1, 4]), Ordering::Greater);
+        assert_eq!(super::compare(&[0, 7, 398], &[0, 10]), Ordering::Less);
+    }
+
+    #[test]
+    fn matches() {
+        let cases = [
+            (">=1.4.100", "1.4.101+v230319", true),
+            (">=1.4.101", "1.4.101+v230319", true),
+            (">=1.4.102", "1.4.101+v230319", false),
+            (">1.4.101", "1.4.101+v230319", false),
+            (">1.4.101", "1.4.102+v230326", true),
+            ("<=1.4.101", "1.4.101+v230319", true),
+            ("<=1.4.100", "1.4.101+v230319", false),
+            ("<1.4.101", "1.4.101+v230319", false),
+            ("<1.4.102", "1.4.101+v230319", true),
+            ("=1.4.101", "1.4.101+v230319", true),
+            ("1.4.101", "1.4.101+v230319", true),
+            ("1.4.100", "1.4.101+v230319", false),
+            ("~0.7.0", "0.7.398+fe2125a-stable", true),
+            ("~0.7.400", "0.7.398+fe2125a-stable", false),
+            ("~0.6.0", "0.7.398+fe2125a-stable", false),
+            ("^0.7.0", "0.7.398+fe2125a-stable", true),
+            ("^0.6.0", "0.7.398+fe2125a-stable", true),
+            ("^1.0.0", "0.7.398+fe2125a-stable", false),
+            ("1.4.x", "1.4.101+v230319", true),
+            ("1.4.*", "1.4.101+v230319", true),
+            ("1.x", "1.4.101+v230319", true),
+            ("1.3.x", "1.4.101+v230319", false),
+            ("*", "1.4.101+v230319", true),
+            ("x", "0.7.398+fe2125a-stable", true),
+            (">=1.4.100 <1.4.110", "1.4.101+v230319", true),
+            (">=1.4.100 <1.4.101", "1.4.101+v230319", false),
+            (">=0.7.398 <0.8", "0.7.398+fe2125a-stable", true),
+            // versions and bounds that cannot be compared match
+            (">=1.4.100", "beta-3", true),
+            (">=beta", "1.4.101+v230319", true),
+        ];
+        for (predicate, version, expected) in cases {
+            assert_eq!(
+                super::matches(predicate, version),
+                expected,
+                "`{version}` matches `{predicate}`"
+            );
+        }
+    }
+}
@@ -0,0 +1,390 @@
+use std::fmt::{self, Display};
+
+use lazy_regex::{regex_captures, regex_is_match};
+
+use crate::log::{self, Level};
+
+/// A known cause of a failed extraction, recognized from the logs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// the mod depends on another mod which is not available
+    MissingDependency { mod_id: String },
+    /// a settings class could not be found, at compile time or at run time
+    MissingSettingsClass { class: String },
+    /// some other class could not be found at run time
+    MissingClass { class: String },
+    /// the printer or accessor mixins failed to compile
+    CompileError { message: String },
+    /// a mixin could not be applied
+    MixinFailure { mixin: String },
+    /// the mod or a dependency was compiled for a newer Java version
+    WrongJavaVersion { required: Option<u32> },
+    /// the mod requires a newer Fabric loader
+    LoaderTooOld { required: String },
+    /// the server did not accept the EULA
+    Eula,
+    /// the server port is already in use
+    PortInUse,
+}
+
+/// A problem together with a suggestion on how to fix it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnosis {
+    pub problem: Problem,
+    /// a suggested change to the mod's entry in `mods.toml`, if applicable
+    pub suggestion: Option<String>,
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::MissingDependency { mod_id } => {
+                write!(f, "required mod `{mod_id}` is missing")
+            }
+            Problem::MissingSettingsClass { class } => {
+                write!(f, "settings class `{class}` could not be found")
+            }
+            Problem::MissingClass { class } => write!(f, "class `{class}` could not be found"),
+            Problem::CompileError { message } => write!(f, "printer failed to compile: {message}"),
+            Problem::MixinFailure { mixin } => write!(f, "mixin `{mixin}` could not be applied"),
+            Problem::WrongJavaVersion {
+                required: Some(version),
+            } => write!(f, "Java {version} or newer is required"),
+            Problem::WrongJavaVersion { required: None } => {
+                write!(f, "a newer Java version is required")
+            }
+            Problem::LoaderTooOld { required } => {
+                write!(f, "Fabric loader version {required} is required")
+            }
+            Problem::Eula => write!(f, "the EULA was not accepted"),
+            Problem::PortInUse => write!(f, "the server port is already in use"),
+        }
+    }
+}
+
+/// Scans the output of a failed extraction for known problems.
+pub fn diagnose(
+    stdout_log: &[String],
+    stderr: &str,
+    settings_classes: &[String],
+) -> Vec<Diagnosis> {
+    let mut diagnoses: Vec<Diagnosis> = vec![];
+    for line in stdout_log.iter().map(String::as_str).chain(stderr.lines()) {
+        let Some(diagnosis) = diagnose_line(line, settings_classes) else {
+            continue;
+        };
+        if !diagnoses.contains(&diagnosis) {
+            diagnoses.push(diagnosis);
+        }
+    }
+    diagnoses
+}
+
+fn diagnose_line(line: &str, settings_classes: &[String]) -> Option<Diagnosis> {
+    let (problem, suggestion) = if let Some((_, required)) = regex_captures!(
+        r"requires (?:version )?(\S+)(?: or later)? of (?:mod )?'?(?:fabricloader|Fabric Loader)\b",
+        line
+    ) {
+        let version = required.trim_start_matches(['>', '<', '=', '~', '^']);
+        (
+            Problem::LoaderTooOld {
+                required: required.to_owned(),
+            },
+            Some(format!("loader_version = \"{version}\"")),
+        )
+    } else if let Some((_, version)) = regex_captures!(
+        r"requires version (\d+)\S*(?: or later)? of (?:mod )?(?:java\b|'[^']*' \(java\))",
+        line
+    ) {
+        (
+            Problem::WrongJavaVersion {
+                required: version.parse().ok(),
+            },
+            None,
+        )
+    } else if let Some(mod_id) = regex_captures!(
+        r"requires (?:version .+? |any version )of (?:mod )?(?:'[^']*' \(([\w-]+)\)|'?([\w-]+)'?).*which is missing",
+        line
+    )
+    .map(|(_, id_with_name, id)| if id_with_name.is_empty() { id } else { id_with_name })
+    .or_else(|| {
+        regex_captures!(r"Could not find required mod: \S+ requires \{([\w-]+)", line)
+            .map(|(_, id)| id)
+    }) {
+        (
+            Problem::MissingDependency {
+                mod_id: mod_id.to_owned(),
+            },
+            Some(format!(
+                "dependencies = [\"maven.modrinth:{mod_id}:<version>\"]"
+            )),
+        )
+    } else if let Some((_, class)) = regex_captures!(
+        r"(?:ClassNotFoundException|NoClassDefFoundError): ([\w$./]+)",
+        line
+    ) {
+        let class = class.replace('/', ".");
+        if settings_classes.contains(&class) {
+            (
+                Problem::MissingSettingsClass { class },
+                Some("settings_classes = [\"<fully qualified class name>\"]".to_owned()),
+            )
+        } else {
+            (Problem::MissingClass { class }, None)
+        }
+    } else if let Some((_, message)) = regex_captures!(
+        r"(?:Printer|SettingsManagerAccessor\d*)\.java:\d+: error: (.+)",
+        line
+    ) {
+        let missing_settings_class = settings_classes.iter().find(|class| {
+            class.rsplit_once('.').is_some_and(|(package, _)| {
+                message.contains(class.as_str())
+                    || message == format!("package {package} does not exist")
+            })
+        });
+        match missing_settings_class {
+            Some(class) => (
+                Problem::MissingSettingsClass {
+                    class: class.clone(),
+                },
+                Some("settings_classes = [\"<fully qualified class name>\"]".to_owned()),
+            ),
+            None => (
+                Problem::CompileError {
+                    message: message.to_owned(),
+                },
+                Some("printer_version = \"<printer matching the Carpet API>\"".to_owned()),
+            ),
+        }
+    } else if let Some((_, mixin)) = regex_captures!(
+        r"Mixin apply (?:for mod \S+ )?failed (?:[\w.-]+\.json:)?([\w$.]+)",
+        line
+    ) {
+        let suggestion = if mixin.contains("SettingsManagerAccessor") {
+            "settings_manager = \"<fully qualified path of the settings manager field>\""
+        } else {
+            "minecraft_version = \"<version the mod was built for>\""
+        };
+        (
+            Problem::MixinFailure {
+                mixin: mixin.to_owned(),
+            },
+            Some(suggestion.to_owned()),
+        )
+    } else if let Some((_, class_version)) = regex_captures!(
+        r"(?:class file version|Unsupported class file major version) (\d+)",
+        line
+    ) {
+        (
+            Problem::WrongJavaVersion {
+                required: class_version
+                    .parse::<u32>()
+                    .ok()
+                    .map(|v| v.saturating_sub(44)),
+            },
+            None,
+        )
+    } else if let Some((_, version)) = regex_captures!(
+        r"(?:requires Java|requires at least JVM runtime version) (\d+)",
+        line
+    ) {
+        (
+            Problem::WrongJavaVersion {
+                required: version.parse().ok(),
+            },
+            None,
+        )
+    } else if regex_is_match!(r"You need to agree to the EULA", line) {
+        (Problem::Eula, None)
+    } else if regex_is_match!(r"(?i)failed to bind to port|address already in use", line) {
+        (Problem::PortInUse, None)
+    } else {
+        return None;
+    };
+    Some(Diagnosis {
+        problem,
+        suggestion,
+    })
+}
+
+/// Prints all diagnoses with their suggestions.
+pub fn print(diagnoses: &[Diagnosis]) {
+    if diagnoses.is_empty() {
+        log::event(
+            Level::Warn,
+            "33",
+            ">> ",
+            format_args!("no known cause found in the logs"),
+        );
+        return;
+    }
+    log::event(Level::Warn, "1;33", ">> ", format_args!("diagnosis"));
+    for Diagnosis {
+        problem,
+        suggestion,
+    } in diagnoses
+    {
+        log::warning!("{problem}");
+        if let Some(suggestion) = suggestion {
+            log::event(
+                Level::Warn,
+                "33",
+                "  ",
+                format_args!("try setting `{suggestion}` in mods.toml"),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnosis(problem: Problem, suggestion: Option<&str>) -> Option<Diagnosis> {
+        Some(Diagnosis {
+            problem,
+            suggestion: suggestion.map(str::to_owned),
+        })
+    }
+
+    #[test]
+    fn diagnose_line() {
+        let settings_classes = ["carpetextra.CarpetExtraSettings".to_owned()];
+        let missing_dependency = |mod_id: &str| {
+            diagnosis(
+                Problem::MissingDependency {
+                    mod_id: mod_id.to_owned(),
+                },
+                Some(&format!(
+                    "dependencies = [\"maven.modrinth:{mod_id}:<version>\"]"
+                )),
+            )
+        };
+        let loader_too_old = |required: &str, version: &str| {
+            diagnosis(
+                Problem::LoaderTooOld {
+                    required: required.to_owned(),
+                },
+                Some(&format!("loader_version = \"{version}\"")),
+            )
+        };
+        let java = |required| diagnosis(Problem::WrongJavaVersion { required }, None);
+        let missing_settings_class = diagnosis(
+            Problem::MissingSettingsClass {
+                class: "carpetextra.CarpetExtraSettings".to_owned(),
+            },
+            Some("settings_classes = [\"<fully qualified class name>\"]"),
+        );
+        let cases = [
+            // Fabric Loader 0.14
+            (
+                "\t - Mod 'Carpet Extra' (carpet-extra) 1.4.128 requires any version of carpet, which is missing!",
+                missing_dependency("carpet"),
+            ),
+            (
+                "\t - Mod 'Carpet Extra' (carpet-extra) 1.4.128 requires version 1.4.128 or later of carpet, which is missing!",
+                missing_dependency("carpet"),
+            ),
+            (
+                "\t - Mod 'Carpet' (carpet) 1.4.128 requires version 0.15.0 or later of fabricloader, but only the wrong version is present: 0.14.21!",
+                loader_too_old("0.15.0", "0.15.0"),
+            ),
+            (
+                "\t - Mod 'Carpet' (carpet) 1.4.128 requires version 17 or later of java, but only the wrong version is present: 8!",
+                java(Some(17)),
+            ),
+            // Fabric Loader 0.15
+            (
+                "\t - Mod 'Carpet Extra' (carpet-extra) 1.4.128 requires any version of mod 'Carpet Mod' (carpet), which is missing!",
+                missing_dependency("carpet"),
+            ),
+            (
+                "\t - Mod 'Carpet' (carpet) 1.4.141 requires version 0.15.0 or later of mod 'Fabric Loader' (fabricloader), but only the wrong version is present: 0.14.21!",
+                loader_too_old("0.15.0", "0.15.0"),
+            ),
+            (
+                "\t - Mod 'Carpet' (carpet) 1.4.141 requires version 21 or later of 'OpenJDK 64-Bit Server VM' (java), but only the wrong version is present: 17!",
+                java(Some(21)),
+            ),
+            // Fabric Loader 0.11
+            (
+                "net.fabricmc.loader.discovery.ModResolutionException: Could not find required mod: carpet-extra requires {carpet @ [>=1.4.0]}",
+                missing_dependency("carpet"),
+            ),
+            // class loading
+            (
+                "Caused by: java.lang.NoClassDefFoundError: carpetextra/CarpetExtraSettings",
+                missing_settings_class.clone(),
+            ),
+            (
+                "Caused by: java.lang.ClassNotFoundException: me.fallenbreath.tweakermore.TweakerMoreMod",
+                diagnosis(
+                    Problem::MissingClass {
+                        class: "me.fallenbreath.tweakermore.TweakerMoreMod".to_owned(),
+                    },
+                    None,
+                ),
+            ),
+            (
+                "java.lang.UnsupportedClassVersionError: carpet/CarpetServer has been compiled by a more recent version of the Java Runtime (class file version 61.0), this version of the Java Runtime only recognizes class file versions up to 52.0",
+                java(Some(17)),
+            ),
+            ("Unsupported class file major version 65", java(Some(21))),
+            (
+                "> Dependency requires at least JVM runtime version 17. This build uses a Java 8 JVM.",
+                java(Some(17)),
+            ),
+            // javac
+            (
+                "/home/runner/work/carpet-database/carpet-database/tmp/active/src/main/java/Printer.java:35: error: package carpetextra does not exist",
+                missing_settings_class,
+            ),
+            (
+                "/home/runner/work/carpet-database/carpet-database/tmp/active/src/main/java/Printer.java:1: error: package carpet.api.settings does not exist",
+                diagnosis(
+                    Problem::CompileError {
+                        message: "package carpet.api.settings does not exist".to_owned(),
+                    },
+                    Some("printer_version = \"<printer matching the Carpet API>\""),
+                ),
+            ),
+            // Mixin
+            (
+                "[main/ERROR] (Minecraft) Mixin apply for mod carpet-extra failed carpet-extra.mixins.json:ServerPlayerEntity_Mixin from mod carpet-extra -> net.minecraft.class_3222: org.spongepowered.asm.mixin.injection.throwables.InvalidInjectionException: Critical injection failure",
+                diagnosis(
+                    Problem::MixinFailure {
+                        mixin: "ServerPlayerEntity_Mixin".to_owned(),
+                    },
+                    Some("minecraft_version = \"<version the mod was built for>\""),
+                ),
+            ),
+            (
+                "[main/ERROR] (Minecraft) Mixin apply for mod data-extractor failed data-extractor.mixins.json:PrivateSettingsManagerAccessor0 from mod data-extractor -> carpetextra.CarpetExtraServer: org.spongepowered.asm.mixin.transformer.throwables.InvalidMixinException: Shadow field settingsManager was not located in the target class",
+                diagnosis(
+                    Problem::MixinFailure {
+                        mixin: "PrivateSettingsManagerAccessor0".to_owned(),
+                    },
+                    Some("settings_manager = \"<fully qualified path of the settings manager field>\""),
+                ),
+            ),
+            // server
+            (
+                "[Server thread/INFO] (Minecraft) You need to agree to the EULA in order to run the server. Go to eula.txt for more info.",
+                diagnosis(Problem::Eula, None),
+            ),
+            (
+                "[Server thread/WARN] (Minecraft) **** FAILED TO BIND TO PORT!",
+                diagnosis(Problem::PortInUse, None),
+            ),
+            ("java.net.BindException: Address already in use", diagnosis(Problem::PortInUse, None)),
+            ("> Task :runServer", None),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(
+                super::diagnose_line(line, &settings_classes),
+                expected,
+                "{line}"
+            );
+        }
+    }
+}
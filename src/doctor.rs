@@ -0,0 +1,273 @@
+use std::{collections::BTreeSet, path::Path, process::Command, time::Duration};
+
+use anyhow::{bail, Result};
+use xshell::Shell;
+
+use crate::{
+    java::{self, JavaVersion},
+    log::{self, Level},
+    process,
+    schema::Mod,
+    util, CLIENT, TEMPLATES_DIR, WORKSPACE_DIR,
+};
+
+/// Below this much free disk space, a warning is shown. Gradle caches and Minecraft runs
+/// easily take a few GiB.
+const MIN_FREE_SPACE: u64 = 5 * 1024 * 1024 * 1024;
+const MIRROR_TIMEOUT: Duration = Duration::from_secs(10);
+/// All hosts that are contacted while generating templates and extracting.
+const MIRRORS: &[(&str, &str)] = &[
+    ("Modrinth API", "https://api.modrinth.com/v2/"),
+    ("Modrinth Maven", "https://api.modrinth.com/maven/"),
+    ("CurseForge Maven", "https://cursemaven.com/"),
+    ("JitPack", "https://jitpack.io/"),
+    (
+        "Fallen-Breath's Maven",
+        "https://maven.fallenbreath.me/releases/",
+    ),
+    ("Fabric Maven", "https://maven.fabricmc.net/"),
+    ("Quilt Meta", "https://meta.quiltmc.org/"),
+    (
+        "Quilt Maven",
+        "https://maven.quiltmc.org/repository/release/",
+    ),
+    ("GitHub", "https://github.com/"),
+    ("Deno", "https://deno.land/"),
+];
+
+enum Outcome {
+    Ok(String),
+    /// something that might cause problems, with a suggested fix
+    Warn(String, String),
+    /// something that will cause problems, with a suggested fix
+    Fail(String, String),
+}
+
+/// Checks all prerequisites for generating templates and extracting rules.
+pub async fn doctor(sh: &Shell, mods: &[Mod]) -> Result<()> {
+    log::header!("checking the environment");
+    let mut failures = 0;
+    let mut report = |name: &str, outcome: Outcome| match outcome {
+        Outcome::Ok(detail) => log::event(
+            Level::Info,
+            "32",
+            "ok    ",
+            format_args!("{name}: {detail}"),
+        ),
+        Outcome::Warn(detail, fix) => {
+            log::event(
+                Level::Warn,
+                "33",
+                "warn  ",
+                format_args!("{name}: {detail}"),
+            );
+            log::event(Level::Warn, "33", "      ", format_args!("fix: {fix}"));
+        }
+        Outcome::Fail(detail, fix) => {
+            failures += 1;
+            log::event(
+                Level::Error,
+                "1;31",
+                "fail  ",
+                format_args!("{name}: {detail}"),
+            );
+            log::event(Level::Error, "31", "      ", format_args!("fix: {fix}"));
+        }
+    };
+
+    // templates are only generated when missing, so git and deno are only needed then
+    let used_templates = mods
+        .iter()
+        .flat_map(|mod_| mod_.versions.values().map(|ver| mod_.template_name(ver)))
+        .collect::<BTreeSet<_>>();
+    let missing_templates = used_templates
+        .iter()
+        .filter(|name| !TEMPLATES_DIR.join(name).join("gradlew").is_file())
+        .cloned()
+        .collect::<Vec<_>>();
+    log::step!("templates");
+    report(
+        "templates",
+        if missing_templates.is_empty() {
+            Outcome::Ok(format!("all {} generated", used_templates.len()))
+        } else {
+            Outcome::Warn(
+                format!("missing for {}", missing_templates.join(", ")),
+                "they are generated on the next run, which requires git and deno".to_owned(),
+            )
+        },
+    );
+
+    log::step!("tools");
+    for (tool, install) in [
+        ("git", "install git from https://git-scm.com"),
+        ("deno", "install deno from https://deno.land"),
+    ] {
+        report(
+            tool,
+            match tool_version(tool) {
+                Some(version) => Outcome::Ok(version),
+                None if missing_templates.is_empty() => Outcome::Warn(
+                    "not found, but only needed to generate templates".to_owned(),
+                    install.to_owned(),
+                ),
+                None => Outcome::Fail("not found".to_owned(), install.to_owned()),
+            },
+        );
+    }
+    if mods.iter().any(|mod_| {
+        mod_.run_client
+            || mod_
+                .versions
+                .values()
+                .any(|ver| ver.run_client == Some(true))
+    }) && !process::has_display()
+    {
+        report(
+            "xvfb-run",
+            match process::find_executable("xvfb-run") {
+                Some(path) => Outcome::Ok(format!("found at '{}'", path.display())),
+                None => Outcome::Fail(
+                    "not found, but needed to run clients without a display".to_owned(),
+                    "install Xvfb".to_owned(),
+                ),
+            },
+        );
+    }
+
+    if cfg!(target_os = "linux") {
+        report(
+            "bwrap",
+            match process::find_executable("bwrap") {
+                Some(path) => Outcome::Ok(format!("found at '{}'", path.display())),
+                None => Outcome::Warn(
+                    "not found, so `--sandbox` only works with `--sandbox-network-only`".to_owned(),
+                    "install bubblewrap".to_owned(),
+                ),
+            },
+        );
+    }
+
+    log::step!("JDKs");
+    let java_versions = mods
+        .iter()
+        .flat_map(|mod_| mod_.versions.values())
+        .map(|ver| match ver.java_version {
+            Some(version) => JavaVersion::Exact(version),
+            None => JavaVersion::minimum(ver.minecraft_version.java_version()),
+        })
+        .collect::<BTreeSet<_>>();
+    for version in java_versions {
+        report(
+            &version.to_string(),
+            match java::find_jdk(version) {
+                Ok(jdk) => Outcome::Ok(format!(
+                    "found Java {} at '{}'",
+                    jdk.version,
+                    jdk.home.display()
+                )),
+                Err(_) => Outcome::Fail(
+                    "not found".to_owned(),
+                    format!("install {version} or set `JAVA_HOME_<version>` to its home dir"),
+                ),
+            },
+        );
+    }
+
+    log::step!("file system");
+    let tmp_dir = WORKSPACE_DIR.join("tmp");
+    let probe = tmp_dir.join(".doctor");
+    report(
+        "tmp dir",
+        match sh
+            .create_dir(&tmp_dir)
+            .and_then(|_| sh.write_file(&probe, ""))
+            .and_then(|_| sh.remove_path(&probe))
+        {
+            Ok(()) => Outcome::Ok(format!("'{}' is writable", tmp_dir.display())),
+            Err(err) => Outcome::Fail(
+                format!("'{}' is not writable: {err}", tmp_dir.display()),
+                "check the permissions of the workspace".to_owned(),
+            ),
+        },
+    );
+    if let Some(free) = free_space(&WORKSPACE_DIR) {
+        let detail = format!("{} free", util::format_size(free));
+        report(
+            "disk space",
+            if free < MIN_FREE_SPACE {
+                Outcome::Warn(
+                    detail,
+                    "free up some space, for example with the `gc` command".to_owned(),
+                )
+            } else {
+                Outcome::Ok(detail)
+            },
+        );
+    }
+
+    log::step!("network");
+    for (name, url) in MIRRORS {
+        let outcome = match CLIENT.get(*url).timeout(MIRROR_TIMEOUT).send().await {
+            // any response means the host is reachable
+            Ok(res) => Outcome::Ok(format!("'{url}' responded with {}", res.status())),
+            Err(err) => Outcome::Fail(
+                format!("'{url}' is not reachable: {err}"),
+                "check the network connection and proxy settings".to_owned(),
+            ),
+        };
+        report(name, outcome);
+    }
+
+    if failures > 0 {
+        bail!("{failures} checks failed");
+    }
+    log::event(
+        Level::Info,
+        "1;32",
+        ">>> ",
+        format_args!("everything looks good"),
+    );
+    Ok(())
+}
+
+/// Returns the first line of `<tool> --version`, if the tool can be run.
+fn tool_version(tool: &str) -> Option<String> {
+    let output = Command::new(tool).arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .next()?
+            .trim()
+            .to_owned(),
+    )
+}
+
+/// Returns the free disk space available to this user on the file system of the path.
+fn free_space(path: &Path) -> Option<u64> {
+    #[cfg(unix)]
+    {
+        use std::{ffi::CString, mem::MaybeUninit, os::unix::ffi::OsStrExt};
+
+        let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+        let mut stat = MaybeUninit::<libc::statvfs>::uninit();
+        // SAFETY: `path` is a valid C string and `stat` is only read after it was written
+        // successfully
+        let stat = unsafe {
+            if libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) != 0 {
+                return None;
+            }
+            stat.assume_init()
+        };
+        #[allow(clippy::unnecessary_cast)]
+        Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        None
+    }
+}
@@ -0,0 +1,135 @@
+use std::{collections::BTreeSet, fs, path::Path};
+
+use anyhow::{Context, Result};
+use xshell::Shell;
+
+use crate::{
+    log::{self, Level},
+    schema::{Loader, Mod},
+    util, ACTIVE_DIR, DATA_DIR, LOGS_DIR, METRICS_DIR, TEMPLATES_DIR,
+};
+
+/// Removes data and metrics files, logs, and template mods which are no longer referenced by
+/// `mods.toml`, as well as the leftovers of the last extraction.
+pub fn gc(sh: &Shell, mods: &[Mod], dry_run: bool) -> Result<()> {
+    log::header!(
+        "collecting garbage{}",
+        if dry_run { " (dry run)" } else { "" }
+    );
+    let mut reclaimed = 0;
+
+    log::step!("removing orphaned data and metrics files");
+    let used_data_files = mods
+        .iter()
+        .flat_map(|mod_| {
+            mod_.versions
+                .keys()
+                .map(|mc_major| format!("{}-{mc_major}.json", mod_.slug))
+        })
+        .chain(["combined.json".to_owned(), "loggers.json".to_owned()])
+        .collect::<BTreeSet<_>>();
+    let metrics_files = if METRICS_DIR.is_dir() {
+        sh.read_dir(&*METRICS_DIR)?
+    } else {
+        vec![]
+    };
+    for path in sh.read_dir(&*DATA_DIR)?.into_iter().chain(metrics_files) {
+        let is_used = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| used_data_files.contains(name));
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "json") && !is_used {
+            reclaimed += remove(sh, &path, dry_run)?;
+        }
+    }
+
+    log::step!("removing orphaned logs");
+    let used_log_dirs = mods
+        .iter()
+        .flat_map(|mod_| {
+            mod_.versions
+                .keys()
+                .map(|mc_major| format!("{}-{mc_major}", mod_.slug))
+        })
+        .collect::<BTreeSet<_>>();
+    if LOGS_DIR.is_dir() {
+        for path in sh.read_dir(&*LOGS_DIR)? {
+            let is_used = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| used_log_dirs.contains(name));
+            if !is_used {
+                reclaimed += remove(sh, &path, dry_run)?;
+            }
+        }
+    }
+
+    log::step!("removing unused template mods");
+    // Quilt templates are converted from the Fabric ones, so those are kept as well
+    let used_templates = mods
+        .iter()
+        .flat_map(|mod_| mod_.versions.values().map(move |ver| (mod_, ver)))
+        .flat_map(|(mod_, ver)| {
+            [
+                mod_.template_name(ver),
+                Loader::Fabric.template_name(ver.minecraft_version),
+            ]
+        })
+        .collect::<BTreeSet<_>>();
+    if TEMPLATES_DIR.is_dir() {
+        for path in sh.read_dir(&*TEMPLATES_DIR)? {
+            let is_used = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| used_templates.contains(name));
+            if path.is_dir() && !is_used {
+                reclaimed += remove(sh, &path, dry_run)?;
+            }
+        }
+    }
+
+    // the active mod dir is recreated for every extraction, including its Gradle caches
+    log::step!("removing previous active mod dir");
+    if ACTIVE_DIR.exists() {
+        reclaimed += remove(sh, &ACTIVE_DIR, dry_run)?;
+    }
+
+    log::event(
+        Level::Info,
+        "1;32",
+        ">>> ",
+        format_args!(
+            "{} {}",
+            if dry_run {
+                "would reclaim"
+            } else {
+                "reclaimed"
+            },
+            util::format_size(reclaimed)
+        ),
+    );
+
+    Ok(())
+}
+
+/// Removes a file or directory, unless in dry run mode, and returns its size in bytes.
+fn remove(sh: &Shell, path: &Path, dry_run: bool) -> Result<u64> {
+    let size = if path.is_dir() {
+        fs_extra::dir::get_size(path)
+            .with_context(|| format!("could not get size of '{}'", path.display()))?
+    } else {
+        fs::metadata(path)
+            .with_context(|| format!("could not get size of '{}'", path.display()))?
+            .len()
+    };
+    log::info!(
+        "{} '{}' ({})",
+        if dry_run { "would remove" } else { "removing" },
+        path.display(),
+        util::format_size(size)
+    );
+    if !dry_run {
+        sh.remove_path(path)?;
+    }
+    Ok(size)
+}
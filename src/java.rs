@@ -0,0 +1,149 @@
+use std::{
+    env,
+    fmt::{self, Display},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Result};
+use itertools::Itertools;
+use lazy_regex::{regex_captures, regex_is_match};
+use once_cell::sync::Lazy;
+
+/// An installed JDK.
+#[derive(Debug, Clone)]
+pub struct Jdk {
+    /// the major Java version, like `8` or `17`
+    pub version: u32,
+    pub home: PathBuf,
+}
+
+/// The lowest Java version used to build the template mods, as Loom 1.x requires Java 17.
+const MIN_BUILD_VERSION: u32 = 17;
+
+/// All JDKs found on this system, in order of preference.
+static JDKS: Lazy<Vec<Jdk>> = Lazy::new(find_jdks);
+
+/// The Java version to extract a mod version with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum JavaVersion {
+    /// the lowest installed version that is at least this, see [`JavaVersion::minimum`]
+    AtLeast(u32),
+    /// exactly this version, as set by the `java_version` field
+    Exact(u32),
+}
+
+impl JavaVersion {
+    /// Returns the requirement for a minimum version, which is raised to [`MIN_BUILD_VERSION`].
+    pub fn minimum(version: u32) -> Self {
+        JavaVersion::AtLeast(version.max(MIN_BUILD_VERSION))
+    }
+}
+
+impl Display for JavaVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JavaVersion::AtLeast(version) => write!(f, "Java {version} or newer"),
+            JavaVersion::Exact(version) => write!(f, "Java {version}"),
+        }
+    }
+}
+
+/// Returns the JDK to use for the given Java version.
+pub fn find_jdk(version: JavaVersion) -> Result<&'static Jdk> {
+    let (found, required) = match version {
+        JavaVersion::AtLeast(version) => {
+            let found = JDKS
+                .iter()
+                .filter(|jdk| jdk.version >= version)
+                .min_by_key(|jdk| jdk.version);
+            (found, version)
+        }
+        JavaVersion::Exact(version) => (JDKS.iter().find(|jdk| jdk.version == version), version),
+    };
+    if let Some(jdk) = found {
+        return Ok(jdk);
+    }
+    let found = if JDKS.is_empty() {
+        "none".to_owned()
+    } else {
+        JDKS.iter()
+            .map(|jdk| format!("Java {} at '{}'", jdk.version, jdk.home.display()))
+            .join(", ")
+    };
+    bail!(
+        "{version} is required, but no matching JDK was found (found: {found}). \
+         Install it or set `JAVA_HOME_{required}` to its home dir"
+    );
+}
+
+/// Looks for JDKs in the `JAVA_HOME_*` variables (as set by `actions/setup-java`), `JAVA_HOME`,
+/// the `java` on the `PATH`, and the usual install locations.
+fn find_jdks() -> Vec<Jdk> {
+    let mut candidates = env::vars_os()
+        .filter(|(key, _)| {
+            key.to_str()
+                .is_some_and(|key| regex_is_match!(r"^JAVA_HOME_\d+(_\w+)?$", key))
+        })
+        .map(|(_, value)| PathBuf::from(value))
+        .sorted()
+        .collect_vec();
+    candidates.extend(env::var_os("JAVA_HOME").map(PathBuf::from));
+    if let Some(path) = env::var_os("PATH") {
+        candidates.extend(
+            env::split_paths(&path)
+                .map(|dir| dir.join("java"))
+                .filter(|java| java.is_file())
+                .filter_map(|java| fs::canonicalize(java).ok())
+                .filter_map(|java| Some(java.parent()?.parent()?.to_path_buf())),
+        );
+    }
+    let home = env::var_os("HOME").map(PathBuf::from);
+    let install_dirs = [
+        Some(PathBuf::from("/usr/lib/jvm")),
+        Some(PathBuf::from("/usr/lib64/jvm")),
+        Some(PathBuf::from("/Library/Java/JavaVirtualMachines")),
+        home.as_ref()
+            .map(|home| home.join(".sdkman/candidates/java")),
+        home.as_ref().map(|home| home.join(".gradle/jdks")),
+    ];
+    for dir in install_dirs.into_iter().flatten() {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        for home in entries.flatten().map(|entry| entry.path()).sorted() {
+            // macOS bundles have the actual home dir inside
+            let bundle_home = home.join("Contents/Home");
+            candidates.push(if bundle_home.is_dir() {
+                bundle_home
+            } else {
+                home
+            });
+        }
+    }
+
+    candidates
+        .into_iter()
+        .filter_map(|home| fs::canonicalize(home).ok())
+        .unique()
+        .filter_map(|home| {
+            Some(Jdk {
+                version: jdk_version(&home)?,
+                home,
+            })
+        })
+        .collect()
+}
+
+/// Reads the major Java version from the `release` file of a JDK.
+fn jdk_version(home: &Path) -> Option<u32> {
+    if !home.join("bin/java").is_file() && !home.join("bin/java.exe").is_file() {
+        return None;
+    }
+    let release = fs::read_to_string(home.join("release")).ok()?;
+    let (_, legacy, major) = regex_captures!(r#"JAVA_VERSION="(?:1\.(\d+)|(\d+))"#m, &release)?;
+    match legacy {
+        "" => major.parse().ok(),
+        _ => legacy.parse().ok(),
+    }
+}
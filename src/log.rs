@@ -0,0 +1,203 @@
+//! Leveled progress output with optional colors and a machine-readable JSON event stream.
+
+use std::{
+    env,
+    fmt::{self, Display},
+    fs::File,
+    io::{IsTerminal, Write},
+    sync::Mutex,
+};
+
+use anyhow::{Context, Result};
+use lazy_regex::regex_replace_all;
+use once_cell::sync::OnceCell;
+use serde_json::{json, Map, Value};
+
+use crate::cli::Args;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, strum::EnumString, strum::AsRefStr,
+)]
+#[strum(serialize_all = "lowercase")]
+pub enum Level {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+/// When to print colored output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum ColorMode {
+    /// colors when stdout is a terminal and `NO_COLOR` is not set
+    Auto,
+    Always,
+    Never,
+}
+
+struct Logger {
+    level: Level,
+    color: bool,
+    /// the file JSON events are written to, one per line
+    json: Option<Mutex<File>>,
+}
+
+static LOGGER: OnceCell<Logger> = OnceCell::new();
+/// The currently entered spans as key-value pairs, from outermost to innermost.
+static SPANS: Mutex<Vec<(&'static str, String)>> = Mutex::new(vec![]);
+
+impl Logger {
+    fn new(level: Level, color: ColorMode, json: Option<File>) -> Self {
+        let color = match color {
+            ColorMode::Auto => {
+                env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+                    && std::io::stdout().is_terminal()
+            }
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        };
+        Self {
+            level,
+            color,
+            json: json.map(Mutex::new),
+        }
+    }
+}
+
+fn logger() -> &'static Logger {
+    LOGGER.get_or_init(|| Logger::new(Level::Info, ColorMode::Auto, None))
+}
+
+/// Configures logging from the `--log-level`, `--quiet`, `--color`, and `--log-json` flags.
+pub fn init(args: &Args) -> Result<()> {
+    let level = match args.parse_value("log-level")? {
+        Some(level) => level,
+        None if args.flag("quiet") => Level::Warn,
+        None => Level::Info,
+    };
+    let color = args.parse_value("color")?.unwrap_or(ColorMode::Auto);
+    let json = args
+        .value("log-json")
+        .map(|path| File::create(path).with_context(|| format!("could not create '{path}'")))
+        .transpose()?;
+    // ignore the error if logging was already used with the defaults before
+    let _ = LOGGER.set(Logger::new(level, color, json));
+    Ok(())
+}
+
+/// Returns whether events of the given level are printed.
+pub fn enabled(level: Level) -> bool {
+    level >= logger().level
+}
+
+/// Wraps the text in the given ANSI SGR code, if colors are enabled.
+pub fn paint(code: &str, text: impl Display) -> String {
+    if logger().color && !code.is_empty() {
+        format!("\x1b[{code}m{text}\x1b[0m")
+    } else {
+        text.to_string()
+    }
+}
+
+/// Prints an event with the given style and records it in the JSON event stream.
+///
+/// Use the macros like [`step!`] instead of calling this directly.
+pub fn event(level: Level, code: &str, prefix: &str, message: fmt::Arguments) {
+    let logger = logger();
+    if let Some(json) = &logger.json {
+        let message = message.to_string();
+        let spans = SPANS
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(key, value)| (key.to_string(), json!(value)))
+            .collect::<Map<String, Value>>();
+        let event = json!({
+            "time": chrono::Utc::now().to_rfc3339(),
+            "level": level.as_ref(),
+            "spans": spans,
+            "message": regex_replace_all!(r"\x1b\[[\d;]*m", &message, ""),
+        });
+        let _ = writeln!(json.lock().unwrap(), "{event}");
+    }
+    if !enabled(level) {
+        return;
+    }
+    let line = paint(code, format_args!("{prefix}{message}"));
+    if level == Level::Error {
+        eprintln!("{line}");
+    } else {
+        println!("{line}");
+    }
+}
+
+/// Disables echoing the command line of an `xshell` command, unless info events are printed.
+pub fn cmd(cmd: xshell::Cmd<'_>) -> xshell::Cmd<'_> {
+    if enabled(Level::Info) {
+        cmd
+    } else {
+        cmd.quiet()
+    }
+}
+
+/// Enters a span, which is attached to all events until the returned guard is dropped.
+pub fn span(key: &'static str, value: impl Display) -> SpanGuard {
+    SPANS.lock().unwrap().push((key, value.to_string()));
+    SpanGuard
+}
+
+pub struct SpanGuard;
+
+impl Drop for SpanGuard {
+    fn drop(&mut self) {
+        SPANS.lock().unwrap().pop();
+    }
+}
+
+/// A top-level section, like getting the rules for one mod version.
+macro_rules! header {
+    ($($arg:tt)*) => {
+        $crate::log::event($crate::log::Level::Info, "1;36", ">>> ", format_args!($($arg)*))
+    };
+}
+
+/// A step inside a section.
+macro_rules! step {
+    ($($arg:tt)*) => {
+        $crate::log::event($crate::log::Level::Info, "36", ">> ", format_args!($($arg)*))
+    };
+}
+
+macro_rules! info {
+    ($($arg:tt)*) => {
+        $crate::log::event($crate::log::Level::Info, "34", "> ", format_args!($($arg)*))
+    };
+}
+
+macro_rules! warning {
+    ($($arg:tt)*) => {
+        $crate::log::event($crate::log::Level::Warn, "33", "> ", format_args!($($arg)*))
+    };
+}
+
+macro_rules! error {
+    ($($arg:tt)*) => {
+        $crate::log::event($crate::log::Level::Error, "1;31", "", format_args!($($arg)*))
+    };
+}
+
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        $crate::log::event($crate::log::Level::Debug, "1;30", "", format_args!($($arg)*))
+    };
+}
+
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        $crate::log::event($crate::log::Level::Trace, "2", "", format_args!($($arg)*))
+    };
+}
+
+pub(crate) use {debug, error, header, info, step, trace, warning};
@@ -0,0 +1,73 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use xshell::Shell;
+
+use crate::{schema::MinecraftMajorVersion, ACTIVE_DIR, LOGS_DIR};
+
+/// How many runs to keep the logs of per mod version, by default.
+pub const DEFAULT_KEEP_LOGS: usize = 10;
+
+/// Files of the active mod which are copied into the log dir, relative to the active mod dir.
+const ARTIFACTS: &[&str] = &[
+    "src/main/java/Printer.java",
+    "build.gradle",
+    "src/main/resources/fabric.mod.json",
+    "src/main/resources/quilt.mod.json",
+    "run/rules.json",
+    "run/loggers.json",
+];
+
+/// The log dir of one extraction at `logs/{slug}-{major}/{timestamp}/`.
+pub struct RunLog {
+    pub dir: PathBuf,
+}
+
+impl RunLog {
+    /// Creates a new log dir for the given mod version and removes the oldest ones, so that at
+    /// most `keep` remain.
+    pub fn create(
+        sh: &Shell,
+        slug: &str,
+        mc_major: &MinecraftMajorVersion,
+        keep: usize,
+    ) -> Result<Self> {
+        let parent = LOGS_DIR.join(format!("{slug}-{mc_major}"));
+        let timestamp = chrono::Utc::now().format("%Y-%m-%dT%H-%M-%SZ").to_string();
+        let dir = parent.join(timestamp);
+        sh.create_dir(&dir)?;
+
+        // timestamps sort chronologically, so the oldest runs come first
+        let mut runs = sh
+            .read_dir(&parent)?
+            .into_iter()
+            .filter(|path| path.is_dir())
+            .collect::<Vec<_>>();
+        runs.sort();
+        for old in runs.iter().take(runs.len().saturating_sub(keep.max(1))) {
+            sh.remove_path(old)?;
+        }
+
+        Ok(Self { dir })
+    }
+
+    /// Writes the output of the Gradle run and copies the generated files and the output of the
+    /// active mod.
+    pub fn save(&self, sh: &Shell, stdout_log: &[String], stderr: &str) -> Result<()> {
+        let stdout = stdout_log
+            .iter()
+            .fold(String::new(), |out, line| out + line + "\n");
+        sh.write_file(self.dir.join("stdout.log"), stdout)?;
+        sh.write_file(self.dir.join("stderr.log"), stderr)?;
+        for artifact in ARTIFACTS {
+            let from = ACTIVE_DIR.join(artifact);
+            if from.is_file() {
+                let to = self.dir.join(Path::new(artifact).file_name().unwrap());
+                sh.copy_file(&from, &to).with_context(|| {
+                    format!("could not copy '{}' to the log dir", from.display())
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,50 +1,98 @@
 use std::{
-    collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet, HashSet},
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, HashSet},
     env,
     fmt::Write as FmtWrite,
-    fs::{self, File},
-    hash::{Hash, Hasher},
+    fs,
     io::{IsTerminal, Write},
     path::{Path, PathBuf},
-    process::Stdio,
+    process::{Command, ExitStatus, Stdio},
+    time::{Duration, Instant},
 };
 
-use anyhow::{bail, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use fs_extra::dir::CopyOptions;
 use itertools::Itertools;
-use lazy_regex::regex_replace;
+use lazy_regex::regex_is_match;
 use once_cell::sync::Lazy;
 use reqwest::Client;
 use schema::{
-    CombinedJson, MinecraftMajorVersion, Mod, ModVersion, ModsToml, PrinterVersion, Rule,
-    VersionSource,
+    CombineReport, CombinedJson, CombinedLoggersJson, Discover, Loader, Logger,
+    MinecraftMajorVersion, MinecraftVersion, Mod, ModVersion, ModsToml, PrinterVersion, Rule,
+    StaleDecision, StaleEntry, VersionSource,
 };
 use serde_json::{json, Map, Value};
-use tokio::{
-    io::{AsyncBufReadExt, AsyncReadExt, BufReader},
-    process::Command,
-};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
 use xshell::{cmd, Shell};
 
-use crate::schema::{RawRule, RulesJson};
+use crate::{
+    java::JavaVersion,
+    log::Level,
+    logs::RunLog,
+    metrics::{ExtractionMetrics, Phase, PhaseTimer, PhaseTiming},
+    process::NoLineWrap,
+    report::{Count, Reporter, Stats},
+    resolve::{IfSet, ResolvedVersion},
+    sandbox::Sandbox,
+    schema::{RawLogger, RawRule, RulesJson},
+    summary::{Status, Summary},
+};
 
+mod cli;
+mod deps;
+mod diagnose;
+mod doctor;
+mod gc;
+mod java;
+mod log;
+mod logs;
+mod matrix;
+mod metrics;
+mod process;
+mod quilt;
+mod report;
+mod resolve;
+mod retry;
+mod sandbox;
 mod schema;
+mod status;
+mod summary;
+mod template;
 #[cfg(feature = "update")]
 mod update;
+mod util;
 
 const TERMINAL_CHILD_STDOUT_LINE_COUNT: usize = 15;
+const DEFAULT_TIMEOUT_SECS: u64 = 30 * 60;
+/// How often memory usage is sampled and the client is checked for having written the rules.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// How long the client may keep running after it has written the rules.
+const CLIENT_QUIT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Printed by all printers when they are called.
+const PRINTER_START_MARKER: &str = "[data-extractor] printing rules";
+/// The slug of Carpet itself, which is the only mod its own loggers are attributed to.
+const CARPET_SLUG: &str = "carpet";
 
 static WORKSPACE_DIR: Lazy<PathBuf> =
     Lazy::new(|| Path::new(env!("CARGO_MANIFEST_DIR")).to_path_buf());
 static ACTIVE_DIR: Lazy<PathBuf> = Lazy::new(|| WORKSPACE_DIR.join("tmp/active"));
 static TEMPLATES_DIR: Lazy<PathBuf> = Lazy::new(|| WORKSPACE_DIR.join("tmp/templates"));
 static DATA_DIR: Lazy<PathBuf> = Lazy::new(|| WORKSPACE_DIR.join("data"));
+static LOGS_DIR: Lazy<PathBuf> = Lazy::new(|| WORKSPACE_DIR.join("logs"));
+static METRICS_DIR: Lazy<PathBuf> = Lazy::new(|| WORKSPACE_DIR.join("metrics"));
 
 static CLIENT: Lazy<Client> = Lazy::new(Client::new);
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    if let Err(err) = try_main().await {
+    // on Ctrl-C the `try_main` future is dropped, which kills any running extraction and restores
+    // the terminal
+    let result = tokio::select! {
+        biased;
+        _ = tokio::signal::ctrl_c() => Err(anyhow!("interrupted")),
+        result = try_main() => result,
+    };
+    if let Err(err) = result {
         std::io::stdout().lock().flush()?;
         return Err(err);
     }
@@ -58,6 +106,46 @@ struct Output {
     minecraft_version: MinecraftMajorVersion,
     version_url: String,
     rules: Vec<RawRule>,
+    loggers: Vec<RawLogger>,
+}
+
+/// What to do with outdated data when only combining.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString)]
+#[strum(serialize_all = "lowercase")]
+enum StaleMode {
+    /// abort the run
+    Fail,
+    /// use the last good data file, or drop the version if there is none
+    Fallback,
+    /// leave the version out of the combined data
+    Drop,
+}
+
+struct RunOptions {
+    /// only combine existing data, see `stale` for what happens with outdated data
+    combine_only: bool,
+    stale: StaleMode,
+    /// record failed extractions and continue with the next mod version
+    keep_going: bool,
+    /// how often to retry an extraction that failed with a transient error
+    gradle_retries: u32,
+    /// maximum duration of a single extraction
+    timeout: Option<Duration>,
+    /// how many runs to keep the logs of per mod version
+    keep_logs: usize,
+    /// only run the mod with this slug
+    only_slug: Option<String>,
+    /// only run the version for this major Minecraft version
+    only_mc_major: Option<MinecraftMajorVersion>,
+    /// run extractions without network access, see [`Sandbox`]
+    sandbox: Option<Sandbox>,
+}
+
+impl RunOptions {
+    fn selects(&self, slug: &str, mc_major: &MinecraftMajorVersion) -> bool {
+        self.only_slug.as_ref().is_none_or(|only| only == slug)
+            && self.only_mc_major.is_none_or(|only| &only == mc_major)
+    }
 }
 
 async fn try_main() -> Result<()> {
@@ -66,60 +154,119 @@ async fn try_main() -> Result<()> {
     // make sure the shell's pwd is always the same
     sh.change_dir(&*WORKSPACE_DIR);
 
-    let arg = env::args().nth(1);
-    let arg = arg.as_ref();
+    let args = cli::Args::parse()?;
+    log::init(&args)?;
+    let arg = args.command.as_deref();
 
     let ModsToml { mods } = toml::from_str(include_str!("../mods.toml"))?;
 
     #[cfg(feature = "update")]
-    if arg.map_or(false, |s| s == "update") {
+    if arg == Some("update") {
         update::search_updates(&mods).await?;
         return Ok(());
     }
 
-    if arg.map_or(false, |s| s == "get-matrix") {
-        let entries = mods
-            .iter()
-            .map(|mod_| json!({ "slug": mod_.slug }))
-            .collect_vec();
-        let matrix = json!({ "include": entries });
-        let mut file = File::options()
-            .append(true)
-            .open(env::var("GITHUB_OUTPUT")?)?;
-        file.write_all(format!("mod-list={}", serde_json::to_string(&matrix)?).as_bytes())?;
+    if arg == Some("doctor") {
+        doctor::doctor(&sh, &mods).await?;
+        return Ok(());
+    }
+
+    if arg == Some("status") {
+        status::status(&sh, &mods)?;
+        return Ok(());
+    }
+
+    if arg == Some("gc") {
+        gc::gc(&sh, &mods, args.flag("dry-run"))?;
+        return Ok(());
+    }
+
+    let reporter = Reporter::from_args(&args)?;
+    if arg == Some("get-matrix") {
+        matrix::get_matrix(&sh, &mods, args.flag("stale-only"), &reporter)?;
         return Ok(());
     }
 
-    if arg.map_or(true, |s| s != "combine") {
-        let used_mc_versions = mods
+    let (only_slug, only_mc_major) = match arg.and_then(|s| s.strip_prefix("mod:")) {
+        Some(selection) => match selection.split_once(':') {
+            Some((slug, mc_major)) => (
+                Some(slug.to_owned()),
+                Some(
+                    mc_major
+                        .parse()
+                        .with_context(|| format!("invalid Minecraft version `{mc_major}`"))?,
+                ),
+            ),
+            None => (Some(selection.to_owned()), None),
+        },
+        None => (None, None),
+    };
+    if let Some(slug) = &only_slug {
+        let mod_ = mods
+            .iter()
+            .find(|mod_| &mod_.slug == slug)
+            .with_context(|| format!("unknown mod `{slug}`"))?;
+        if let Some(mc_major) = only_mc_major {
+            if !mod_.versions.contains_key(&mc_major) {
+                bail!("mod `{slug}` has no version for Minecraft {mc_major}");
+            }
+        }
+    }
+    let options = RunOptions {
+        combine_only: arg == Some("combine"),
+        stale: args
+            .value("stale")
+            .map_or(Ok(StaleMode::Fail), str::parse)
+            .context("invalid value for `--stale`, expected `fail`, `fallback`, or `drop`")?,
+        keep_going: args.flag("keep-going"),
+        gradle_retries: args.parse_value("gradle-retries")?.unwrap_or_default(),
+        timeout: match args.parse_value("timeout")?.unwrap_or(DEFAULT_TIMEOUT_SECS) {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        },
+        keep_logs: args
+            .parse_value("keep-logs")?
+            .unwrap_or(logs::DEFAULT_KEEP_LOGS),
+        only_slug,
+        only_mc_major,
+        sandbox: Sandbox::from_args(&args)?,
+    };
+
+    if !options.combine_only {
+        let used_templates = mods
             .iter()
-            .flat_map(|mod_| mod_.versions.values())
-            .map(|ver| ver.minecraft_version.to_string())
+            .flat_map(|mod_| {
+                mod_.versions
+                    .iter()
+                    .filter(|(mc_major, _)| options.selects(&mod_.slug, mc_major))
+                    .map(|(_, ver)| (ver.minecraft_version, mod_.loader(ver)))
+            })
             .collect::<BTreeSet<_>>();
-        gen_template_mods(&sh, used_mc_versions)?;
+        gen_template_mods(&sh, used_templates).await?;
     }
 
     let mut outputs: Vec<Output> = vec![];
+    let mut summary = Summary::default();
     for mod_ in &mods {
-        if arg
-            .and_then(|s| s.strip_prefix("mod:"))
-            .map_or(true, |slug| slug == mod_.slug)
+        if options
+            .only_slug
+            .as_ref()
+            .is_none_or(|slug| slug == &mod_.slug)
         {
-            run_mod(
-                &sh,
-                mod_,
-                &mut outputs,
-                arg.map_or(false, |s| s == "combine"),
-            )
-            .await
-            .with_context(|| {
-                format!("\x1b[1;31mfailed to extract data for mod `{mod_:#?}`\x1b[0m")
-            })?;
+            run_mod(&sh, mod_, &mut outputs, &mut summary, &options)
+                .await
+                .with_context(|| format!("failed to extract data for mod `{mod_:#?}`"))?;
         }
     }
+    summary.print();
 
-    if arg.map_or(true, |s| s == "combine") {
-        combine(&sh, outputs)?;
+    // the versions that did succeed are combined even when others failed with `--keep-going`
+    if matches!(arg, None | Some("combine")) {
+        combine(&sh, outputs, &summary, &reporter)?;
+    }
+
+    if summary.has_failures() {
+        bail!("{} mod versions failed to extract", summary.failure_count());
     }
 
     Ok(())
@@ -147,30 +294,33 @@ fn modify_file(path: impl AsRef<Path>, func: impl FnOnce(String) -> Result<Strin
     Ok(())
 }
 
-fn gen_template_mods(sh: &Shell, mc_versions: BTreeSet<String>) -> Result<()> {
-    println!("\x1b[1;36m>>> generating template mods for all Minecraft versions\x1b[0m");
+async fn gen_template_mods(
+    sh: &Shell,
+    templates: BTreeSet<(MinecraftVersion, Loader)>,
+) -> Result<()> {
+    log::header!("generating template mods for all Minecraft versions");
 
     // prepare directories
-    println!("\x1b[36m>> preparing the tmp directory\x1b[0m");
+    log::step!("preparing the tmp directory");
     let tmp_dir = WORKSPACE_DIR.join("tmp");
     let clone_dir = tmp_dir.join("fabricmc.net");
     sh.create_dir(&tmp_dir)?;
 
     // clone the fabricmc.net source
-    println!("\x1b[36m>> cloning the fabricmc.net source\x1b[0m");
+    log::step!("cloning the fabricmc.net source");
     if clone_dir.is_dir() {
-        println!("\x1b[34m> directory already exists, skipping clone\x1b[0m");
+        log::info!("directory already exists, skipping clone");
     } else {
-        cmd!(
+        log::cmd(cmd!(
             sh,
             "git clone https://github.com/FabricMC/fabricmc.net {clone_dir}"
-        )
+        ))
         .run()?;
     }
 
     // edit the vite config (we only need the generator lib, overwriting the config avoids
     // downloading unnecessary dependencies)
-    println!("\x1b[36m>> editing the vite config\x1b[0m");
+    log::step!("editing the vite config");
     let vite_root = clone_dir.join("scripts");
     sh.write_file(
         vite_root.join("vite.config.js"),
@@ -192,42 +342,55 @@ export default {
     )?;
 
     // build the library
-    println!("\x1b[36m>> building the generator lib\x1b[0m");
+    log::step!("building the generator lib");
     let cd = sh.push_dir(&vite_root);
-    cmd!(sh, "deno task buildLib").run()?;
+    log::cmd(cmd!(sh, "deno task buildLib")).run()?;
     drop(cd);
 
-    // generate the mod templates for all versions
-    println!("\x1b[36m>> generating the template mods\x1b[0m");
-    cmd!(sh, "deno run -A gen_template_mods.ts {mc_versions...}").run()?;
+    // generate the mod templates for all versions, the Quilt ones are converted from these
+    log::step!("generating the template mods");
+    let mc_versions = templates
+        .iter()
+        .map(|(version, _)| version.to_string())
+        .collect::<BTreeSet<_>>();
+    log::cmd(cmd!(
+        sh,
+        "deno run -A gen_template_mods.ts {mc_versions...}"
+    ))
+    .run()?;
+    for (version, loader) in templates {
+        if loader == Loader::Quilt {
+            quilt::gen_template(sh, version).await?;
+        }
+    }
 
     Ok(())
 }
 
 async fn run_mod(
     sh: &Shell,
-    Mod {
+    mod_: &Mod,
+    outputs: &mut Vec<Output>,
+    summary: &mut Summary,
+    options: &RunOptions,
+) -> Result<()> {
+    let Mod {
         name,
         slug,
         curseforge_slug,
-        project_id,
         repo,
-        entrypoint: default_entrypoint,
-        settings_manager: default_settings_manager,
-        settings_manager_class: default_settings_manager_class,
-        rule_annotation_class: default_rule_annotation_class,
-        settings_classes: default_settings_classes,
-        loader_version: default_loader_version,
-        run_client: default_run_client,
-        common_dependencies,
         versions,
-    }: &Mod,
-    outputs: &mut Vec<Output>,
-    combine_only: bool,
-) -> Result<()> {
-    println!(
-        "\x1b[1;32m{0}\n>>> getting rules for '{name}' <<<\n{0}\x1b[0m",
-        "-".repeat(50)
+        ..
+    } = mod_;
+    let _span = log::span("mod", slug);
+    log::event(
+        Level::Info,
+        "1;32",
+        "",
+        format_args!(
+            "{0}\n>>> getting rules for '{name}' <<<\n{0}",
+            "-".repeat(50)
+        ),
     );
     let curseforge_slug = curseforge_slug.as_ref().unwrap_or(slug);
     let mod_url = match versions
@@ -242,325 +405,763 @@ async fn run_mod(
         }
         VersionSource::GitHub { .. } => format!("https://github.com/{repo}"),
     };
-    for (
-        mc_major,
-        ModVersion {
-            minecraft_version,
-            printer_version,
-            entrypoint,
-            settings_manager,
-            settings_manager_class,
-            rule_annotation_class,
-            settings_classes,
-            loader_version,
-            run_client,
-            dependencies,
-            source,
-        },
-    ) in versions
-    {
-        println!("\x1b[1;36m>>> getting rules for '{name}' for Minecraft {mc_major} using {minecraft_version} with printer {printer_version}\x1b[0m");
-        let entrypoint = entrypoint
-            .as_ref()
-            .or(default_entrypoint.as_ref())
-            .filter(|s| !s.is_empty());
-        let settings_manager = settings_manager
-            .as_ref()
-            .or(default_settings_manager.as_ref())
-            .filter(|s| !s.is_empty());
-        let settings_manager_class = settings_manager_class
-            .as_ref()
-            .or(default_settings_manager_class.as_ref())
-            .filter(|s| !s.is_empty())
-            .map(|s| s.as_str())
-            .unwrap_or(match printer_version {
-                PrinterVersion::V1 | PrinterVersion::V2 => "carpet.settings.SettingsManager",
-                PrinterVersion::V3 => "carpet.api.settings.SettingsManager",
-                PrinterVersion::MagicLibV1 | PrinterVersion::MagicLibV2 => {
-                    "top.hendrixshen.magiclib.carpet.impl.WrappedSettingManager"
-                }
-            });
-        let rule_annotation_class = rule_annotation_class
-            .as_ref()
-            .or(default_rule_annotation_class.as_ref())
-            .filter(|s| !s.is_empty())
-            .map(|s| s.as_str())
-            .unwrap_or(match printer_version {
-                PrinterVersion::V1 | PrinterVersion::V2 => "carpet.settings.Rule",
-                PrinterVersion::V3 => "carpet.api.settings.Rule",
-                PrinterVersion::MagicLibV1 | PrinterVersion::MagicLibV2 => {
-                    "top.hendrixshen.magiclib.carpet.api.annotation.Rule"
-                }
-            });
-        let settings_classes = settings_classes
-            .as_ref()
-            .or(default_settings_classes.as_ref())
-            .with_context(|| "no settings classes specified")?;
-        let loader_version = loader_version
-            .as_ref()
-            .or(default_loader_version.as_ref())
-            .filter(|s| !s.is_empty());
-        let run_client = run_client.unwrap_or(*default_run_client);
-        let dependencies = common_dependencies.iter().chain(dependencies).collect_vec();
-
-        let version_url = match source {
-            VersionSource::Modrinth { version, .. } => {
-                format!("https://modrinth.com/mod/{slug}/version/{version}")
-            }
-            VersionSource::CurseForge { file_id } => {
-                format!(
-                    "https://curseforge.com/minecraft/mc-mods/{curseforge_slug}/files/{file_id}"
-                )
-            }
-            VersionSource::GitHub { tag, .. } => {
-                format!("https://github.com/{repo}/releases/tag/{tag}")
+    for (mc_major, version) in versions {
+        if !options.selects(slug, mc_major) {
+            continue;
+        }
+        let _span = log::span("version", mc_major);
+        match run_mod_version(sh, mod_, &mod_url, mc_major, version, outputs, options)
+            .await
+            .with_context(|| {
+                format!("failed to extract data for '{name}' for Minecraft {mc_major}")
+            }) {
+            Ok(status) => summary.record(slug, *mc_major, status),
+            Err(err) if options.keep_going => {
+                // the summary prints the full error chain
+                log::error!("failed, continuing with the next version");
+                summary.record(slug, *mc_major, Status::Failed(err));
             }
-        };
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
 
-        // skip, if data for version already exists for this version and is non-empty
-        let output_data_file = DATA_DIR.join(format!("{slug}-{mc_major}.json"));
-        let hash = hash((
-            mc_major,
-            minecraft_version,
-            printer_version,
-            entrypoint,
-            settings_manager,
-            settings_manager_class,
-            rule_annotation_class,
-            settings_classes,
-            loader_version,
-            run_client,
-            &dependencies,
-            source,
-        ));
-        if let Some(output) = sh
-            .read_file(&output_data_file)
-            .ok()
-            .and_then(|str| serde_json::from_str::<RulesJson>(&str).ok())
-        {
-            if output.hash == hash && !output.rules.is_empty() {
-                println!("\x1b[34m> data already up-to-date, skipping extraction\x1b[0m");
-                outputs.push(Output {
-                    mod_name: name.clone(),
-                    mod_slug: slug.clone(),
-                    mod_url: mod_url.clone(),
-                    minecraft_version: *mc_major,
-                    version_url,
-                    rules: output.rules,
-                });
-                continue;
-            }
+async fn run_mod_version(
+    sh: &Shell,
+    mod_ @ Mod {
+        name,
+        slug,
+        curseforge_slug,
+        project_id,
+        repo,
+        ..
+    }: &Mod,
+    mod_url: &str,
+    mc_major: &MinecraftMajorVersion,
+    version: &ModVersion,
+    outputs: &mut Vec<Output>,
+    options: &RunOptions,
+) -> Result<Status> {
+    let curseforge_slug = curseforge_slug.as_ref().unwrap_or(slug);
+    log::header!(
+        "getting rules for '{name}' for Minecraft {mc_major} using {} on {} with printer {}",
+        version.minecraft_version,
+        mod_.loader(version),
+        version.printer_version,
+    );
+    let resolved = ResolvedVersion::new(mod_, *mc_major, version)?;
+    let hash = resolved.cache_key();
+    let ResolvedVersion {
+        mc_major: _,
+        minecraft_version,
+        printer_version,
+        entrypoint,
+        settings_managers,
+        settings_manager_class,
+        rule_annotation_class,
+        settings_classes,
+        discover: IfSet(discover),
+        printer_file: IfSet(printer_file),
+        extra_mixins: IfSet(extra_mixins),
+        jvm_args: IfSet(jvm_args),
+        system_properties: IfSet(system_properties),
+        gradle_properties: IfSet(gradle_properties),
+        run_files: IfSet(run_files),
+        loader: IfSet(loader),
+        loader_version,
+        run_client,
+        auto_dependencies: IfSet(auto_dependencies),
+        dependencies,
+        source,
+        format_version: _,
+    } = resolved;
+    let discover = discover.unwrap_or_default();
+    let loader = loader.unwrap_or_default();
+
+    let version_url = match source {
+        VersionSource::Modrinth { version, .. } => {
+            format!("https://modrinth.com/mod/{slug}/version/{version}")
+        }
+        VersionSource::CurseForge { file_id } => {
+            format!("https://curseforge.com/minecraft/mc-mods/{curseforge_slug}/files/{file_id}")
+        }
+        VersionSource::GitHub { tag, .. } => {
+            format!("https://github.com/{repo}/releases/tag/{tag}")
+        }
+    };
+
+    // skip, if data for version already exists for this version and is non-empty
+    let output_data_file = data_file(slug, mc_major);
+    let status = match read_data(sh, slug, mc_major) {
+        Some(output) if output.hash == hash => {
+            log::info!("data already up-to-date, skipping extraction");
+            Some((Status::Skipped, output))
+        }
+        Some(output) if options.combine_only && options.stale == StaleMode::Fallback => {
+            log::warning!("data is outdated, falling back to last good data");
+            Some((Status::FellBack, output))
         }
+        _ => None,
+    };
+    if let Some((status, output)) = status {
+        outputs.push(Output {
+            mod_name: name.clone(),
+            mod_slug: slug.clone(),
+            mod_url: mod_url.to_owned(),
+            minecraft_version: *mc_major,
+            version_url,
+            rules: output.rules,
+            loggers: output.loggers,
+        });
+        return Ok(status);
+    }
 
-        if combine_only {
+    if options.combine_only {
+        if options.stale == StaleMode::Fail {
             bail!("cannot run combine task with outdated data");
         }
+        log::warning!("data is outdated or missing, dropping it");
+        return Ok(Status::Dropped);
+    }
 
-        // remove any previous active mod
-        println!("\x1b[36m>> removing previous active mod dir\x1b[0m");
-        sh.remove_path(&*ACTIVE_DIR)?;
+    let java_version = match version.java_version {
+        Some(java_version) => JavaVersion::Exact(java_version),
+        None => JavaVersion::minimum(minecraft_version.java_version()),
+    };
+    let jdk = java::find_jdk(java_version)?;
+    let java_home = jdk.home.as_path();
+    log::info!("using Java {} from '{}'", jdk.version, java_home.display());
 
-        // copy the respective template
-        println!("\x1b[36m>> copying template mod for Minecraft {minecraft_version}\x1b[0m");
-        let from = TEMPLATES_DIR.join(minecraft_version.to_string());
-        let to = &*ACTIVE_DIR;
-        fs_extra::dir::copy(&from, to, &CopyOptions::new().copy_inside(true)).with_context(
-            || {
-                format!(
-                    "couldn't copy template mod from '{}' to '{}'",
-                    from.display(),
-                    to.display()
-                )
-            },
-        )?;
+    // remove any previous active mod
+    log::step!("removing previous active mod dir");
+    sh.remove_path(&*ACTIVE_DIR)?;
 
-        // set cwd
-        let _cd = sh.push_dir(&*ACTIVE_DIR);
-
-        // write printer class
-        println!("\x1b[36m>> writing printer {printer_version} class\x1b[0m");
-        let mut mixins = vec![];
-        let raw_printer = match printer_version {
-            PrinterVersion::V1 => {
-                // also add accessor mixin
-                sh.write_file(
-                    "src/main/java/mixin/SettingsManagerAccessor.java",
-                    include_str!("../printers/SettingsManagerAccessor.java"),
-                )?;
-                mixins.push("SettingsManagerAccessor");
-
-                include_str!("../printers/V1Printer.java")
-            }
-            PrinterVersion::V2 => include_str!("../printers/V2Printer.java"),
-            PrinterVersion::V3 => include_str!("../printers/V3Printer.java"),
-            PrinterVersion::MagicLibV1 => include_str!("../printers/MagicLibV1Printer.java"),
-            PrinterVersion::MagicLibV2 => include_str!("../printers/MagicLibV2Printer.java"),
-        };
-        if let Some(settings_manager) = settings_manager {
-            let (class_path, field_name) = settings_manager
-                .rsplit_once('.')
-                .with_context(|| format!("invalid settings_manager path '{settings_manager}'"))?;
+    // copy the respective template
+    log::step!("copying template mod for Minecraft {minecraft_version}");
+    let mut timer = PhaseTimer::new();
+    timer.start(Phase::TemplateCopy);
+    let from = TEMPLATES_DIR.join(loader.template_name(minecraft_version));
+    let to = &*ACTIVE_DIR;
+    fs_extra::dir::copy(&from, to, &CopyOptions::new().copy_inside(true)).with_context(|| {
+        format!(
+            "couldn't copy template mod from '{}' to '{}'",
+            from.display(),
+            to.display()
+        )
+    })?;
+    timer.end();
+
+    // set cwd
+    let _cd = sh.push_dir(&*ACTIVE_DIR);
+
+    // write printer class
+    match &printer_file {
+        Some(file) => log::step!("writing custom printer class from '{}'", file.path),
+        None => log::step!("writing printer {printer_version} class"),
+    }
+    let mut mixins = vec![];
+    let builtin_printer = match printer_version {
+        PrinterVersion::V1 => {
+            // also add accessor mixin
             sh.write_file(
-                "src/main/java/mixin/PrivateSettingsManagerAccessor.java",
-                format!(
-                    r###"
-package mixin;
-
-import org.spongepowered.asm.mixin.Mixin;
-import org.spongepowered.asm.mixin.gen.Accessor;
-
-@Mixin({class_path}.class)
-public interface PrivateSettingsManagerAccessor {{
-    @Accessor(value = "{field_name}", remap = false)
-    static {settings_manager_class} getSettingsManager() {{
-        throw new AssertionError();
-    }}
-}}
-"###
-                ),
+                "src/main/java/mixin/SettingsManagerAccessor.java",
+                include_str!("../printers/SettingsManagerAccessor.java"),
             )?;
-            mixins.push("PrivateSettingsManagerAccessor");
-        }
-        let printer = raw_printer
-            .replace(
-                "SETTINGS_MANAGERS",
-                &std::iter::once("carpet.CarpetServer.settingsManager")
-                    .chain(
-                        settings_manager
-                            .is_some()
-                            .then_some("mixin.PrivateSettingsManagerAccessor.getSettingsManager()"),
-                    )
-                    .collect_vec()
-                    .join(", "),
-            )
-            .replace("RULE", rule_annotation_class)
-            .replace(
+            mixins.push("SettingsManagerAccessor".to_owned());
+
+            include_str!("../printers/V1Printer.java")
+        }
+        PrinterVersion::V2 => include_str!("../printers/V2Printer.java"),
+        PrinterVersion::V3 => include_str!("../printers/V3Printer.java"),
+        PrinterVersion::MagicLibV1 => include_str!("../printers/MagicLibV1Printer.java"),
+        PrinterVersion::MagicLibV2 => include_str!("../printers/MagicLibV2Printer.java"),
+    };
+    let raw_printer = printer_file
+        .as_ref()
+        .map_or(builtin_printer, |file| file.content.as_str());
+    for mixin in &extra_mixins {
+        let class_name = mixin.class_name()?;
+        log::info!("adding mixin '{}'", mixin.path);
+        sh.write_file(
+            format!("src/main/java/mixin/{class_name}.java"),
+            &mixin.content,
+        )?;
+        mixins.push(class_name.to_owned());
+    }
+    // one accessor per custom settings manager, so every config file is known
+    let mut settings_manager_getters = vec![];
+    for (index, settings_manager) in settings_managers.iter().enumerate() {
+        let (class_path, field_name) = settings_manager
+            .rsplit_once('.')
+            .with_context(|| format!("invalid settings_manager path '{settings_manager}'"))?;
+        let accessor = format!("PrivateSettingsManagerAccessor{index}");
+        sh.write_file(
+            format!("src/main/java/mixin/{accessor}.java"),
+            template::render(
+                "PrivateSettingsManagerAccessor",
+                include_str!("../printers/PrivateSettingsManagerAccessor.java"),
+                &[
+                    ("ACCESSOR", &accessor),
+                    ("TARGET_CLASS", class_path),
+                    ("FIELD_NAME", field_name),
+                    ("SETTINGS_MANAGER_CLASS", settings_manager_class),
+                ],
+            )?,
+        )?;
+        settings_manager_getters.push(format!("mixin.{accessor}.getSettingsManager()"));
+        mixins.push(accessor);
+    }
+    let settings_managers = match printer_version {
+        // MagicLib printers only work with MagicLib's own settings managers
+        PrinterVersion::MagicLibV1 | PrinterVersion::MagicLibV2 => {
+            if settings_manager_getters.is_empty() {
+                bail!("printer {printer_version} requires a `settings_manager`");
+            }
+            settings_manager_getters
+        }
+        PrinterVersion::V1 | PrinterVersion::V2 | PrinterVersion::V3 => {
+            std::iter::once("carpet.CarpetServer.settingsManager".to_owned())
+                .chain(settings_manager_getters)
+                .collect()
+        }
+    };
+    let render = match printer_file {
+        Some(_) => template::render_subset,
+        None => template::render,
+    };
+    let printer = render(
+        &printer_file.as_ref().map_or_else(
+            || format!("printer {printer_version}"),
+            |file| format!("'{}'", file.path),
+        ),
+        raw_printer,
+        &[
+            ("SETTINGS_MANAGERS", &settings_managers.join(", ")),
+            (
+                "DISCOVER_FROM_MANAGERS",
+                &(discover == Discover::Manager).to_string(),
+            ),
+            ("RULE_ANNOTATION", rule_annotation_class),
+            (
                 "SETTINGS_CLASSES",
                 &settings_classes
                     .iter()
                     .map(|path| format!("{path}.class"))
-                    .collect_vec()
                     .join(", "),
-            );
-        sh.write_file("src/main/java/Printer.java", printer)?;
+            ),
+        ],
+    )?;
+    sh.write_file("src/main/java/Printer.java", printer)?;
+    sh.write_file(
+        "src/main/java/RuleTranslations.java",
+        include_str!("../printers/RuleTranslations.java"),
+    )?;
+    sh.write_file(
+        "src/main/java/LoggerPrinter.java",
+        include_str!("../printers/LoggerPrinter.java"),
+    )?;
 
-        modify_file(
-            ACTIVE_DIR.join("src/main/resources/data-extractor.mixins.json"),
-            |str| {
-                let mut mixins_conf = serde_json::from_str::<Map<String, Value>>(&str)?;
-                mixins_conf["package"] = json!("mixin");
-                mixins_conf["mixins"] = json!(mixins);
-                Ok(serde_json::to_string_pretty(&mixins_conf)?)
-            },
-        )?;
+    modify_file(
+        ACTIVE_DIR.join("src/main/resources/data-extractor.mixins.json"),
+        |str| {
+            let mut mixins_conf = serde_json::from_str::<Map<String, Value>>(&str)?;
+            mixins_conf["package"] = json!("mixin");
+            mixins_conf["mixins"] = json!(mixins);
+            Ok(serde_json::to_string_pretty(&mixins_conf)?)
+        },
+    )?;
 
-        // set entrypoints
-        println!("\x1b[36m>> setting entrypoints\x1b[0m");
-        modify_file(
+    // set entrypoints
+    log::step!("setting entrypoints");
+    let entrypoints = entrypoint
+        .iter()
+        .map(|s| s.as_str())
+        .chain(["carpet.CarpetServer::onGameStarted", "Printer::print"])
+        .collect_vec();
+    match loader {
+        Loader::Fabric => modify_file(
             ACTIVE_DIR.join("src/main/resources/fabric.mod.json"),
             |str| {
                 let mut fabric_conf = serde_json::from_str::<Map<String, Value>>(&str)?;
-                let entrypoints = entrypoint
-                    .iter()
-                    .map(|s| s.as_str())
-                    .chain(["carpet.CarpetServer::onGameStarted", "Printer::print"])
-                    .collect_vec();
                 fabric_conf.insert("entrypoints".to_owned(), json!({ "main": entrypoints }));
                 // also remove all dependencies, as some templates use the wrong modid for
                 // fabric-api
                 fabric_conf.insert("depends".to_owned(), json!({}));
                 Ok(serde_json::to_string_pretty(&fabric_conf)?)
             },
-        )?;
+        )?,
+        // Quilt also runs the Fabric `main` entrypoints
+        Loader::Quilt => modify_file(
+            ACTIVE_DIR.join("src/main/resources/quilt.mod.json"),
+            |str| {
+                let mut quilt_conf = serde_json::from_str::<Value>(&str)?;
+                quilt_conf["quilt_loader"]["entrypoints"] = json!({ "main": entrypoints });
+                quilt_conf["quilt_loader"]["depends"] = json!([]);
+                Ok(serde_json::to_string_pretty(&quilt_conf)?)
+            },
+        )?,
+    }
 
-        // accept EULA
-        println!("\x1b[36m>> accepting the EULA\x1b[0m");
-        sh.write_file("run/eula.txt", "eula=true")?;
+    // accept EULA
+    log::step!("accepting the EULA");
+    sh.write_file("run/eula.txt", "eula=true")?;
 
-        // add dependencies
-        println!("\x1b[36m>> adding dependencies\x1b[0m");
-        let main_mod_dep = match source {
-            VersionSource::Modrinth { version, filename } => {
-                get_modrinth_dep(sh, slug, version, filename).await?
-            }
-            VersionSource::CurseForge { file_id } => {
-                format!("'curse.maven:{slug}-{project_id}:{file_id}'")
+    // place files some mods need to start, like configs
+    if !run_files.is_empty() {
+        log::step!("seeding the run dir");
+        for file in &run_files {
+            log::info!("writing '{}'", file.path.display());
+            sh.write_file(Path::new("run").join(&file.path), &file.content)?;
+        }
+    }
+
+    // add dependencies
+    log::step!("adding dependencies");
+    timer.start(Phase::Download);
+    let main_mod_dep = match source {
+        VersionSource::Modrinth { version, filename } => {
+            get_modrinth_dep(sh, slug, version, filename).await?
+        }
+        VersionSource::CurseForge { file_id } => {
+            format!("'curse.maven:{slug}-{project_id}:{file_id}'")
+        }
+        VersionSource::GitHub { tag, asset } => get_github_dep(sh, repo, tag, asset).await?,
+    };
+    let mut resolved_deps = vec![];
+    // opt-in, so that extracting with complete `dependencies` needs no Modrinth API calls
+    if auto_dependencies.unwrap_or(false) {
+        let jar = get_mod_jar(sh, slug, *project_id, source).await?;
+        for declared in deps::declared(&jar).context("could not read the mod's dependencies")? {
+            if let Some(dep) = dependencies
+                .iter()
+                .find(|dep| deps::dependency_slug(dep) == Some(declared.slug))
+            {
+                log::info!("using explicit dependency '{dep}' for `{}`", declared.id);
+                continue;
             }
-            VersionSource::GitHub { tag, asset } => get_github_dep(sh, repo, tag, asset).await?,
-        };
-        modify_file(ACTIVE_DIR.join("build.gradle"), |str| {
-            let extra_deps = dependencies.iter().fold(String::new(), |mut out, dep| {
+            let dep = deps::resolve(&declared, minecraft_version, loader).await?;
+            log::info!("resolved `{}` to '{dep}'", declared.id);
+            resolved_deps.push(dep);
+        }
+    }
+    modify_file(ACTIVE_DIR.join("build.gradle"), |str| {
+        let extra_deps = dependencies.iter().copied().chain(&resolved_deps).fold(
+            String::new(),
+            |mut out, dep| {
                 let _ = write!(out, "\n    modImplementation '{dep}'");
                 out
-            });
-            Ok(str
-                + &format!(
-                    r###"
-repositories {{
-    // Modrinth maven
-    exclusiveContent {{
-        forRepository {{
-            maven {{ url = "https://api.modrinth.com/maven" }}
-        }}
-        filter {{
-            includeGroup "maven.modrinth"
-        }}
-    }}
-    // jitpack for GitHub
-    maven {{ url = "https://jitpack.io" }}
-    // CurseForge maven
-    exclusiveContent {{
-        forRepository {{
-            maven {{ url = "https://cursemaven.com" }}
-        }}
-        filter {{
-            includeGroup "curse.maven"
-        }}
-    }}
-    // Fallen-Breath's stuff
-    maven {{ url "https://maven.fallenbreath.me/releases" }}
-}}
-
-dependencies {{
-    modImplementation {main_mod_dep}{extra_deps}
-}}
-"###,
-                ))
-        })?;
+            },
+        );
+        Ok(str
+            + &template::render(
+                "appendix.gradle",
+                include_str!("../printers/appendix.gradle"),
+                &[
+                    ("MAIN_MOD_DEPENDENCY", &main_mod_dep),
+                    ("EXTRA_DEPENDENCIES", &extra_deps),
+                    (
+                        "JVM_ARGS",
+                        &jvm_args.iter().map(|arg| groovy_string(arg)).join(", "),
+                    ),
+                    (
+                        "SYSTEM_PROPERTIES",
+                        &match system_properties {
+                            Some(properties) => format!(
+                                "[{}]",
+                                properties
+                                    .iter()
+                                    .map(|(key, value)| format!(
+                                        "{}: {}",
+                                        groovy_string(key),
+                                        groovy_string(value)
+                                    ))
+                                    .join(", ")
+                            ),
+                            None => "[:]".to_owned(),
+                        },
+                    ),
+                ],
+            )?)
+    })?;
+
+    timer.end();
 
-        // overriding loader version
-        if let Some(version) = loader_version {
-            println!("\x1b[36m>> overriding loader version\x1b[0m");
-            modify_file(ACTIVE_DIR.join("gradle.properties"), |str| {
-                Ok(
-                    regex_replace!(r"(loader_version\s*=\s*).*", &str, |_, pre| format!(
-                        "{pre}{version}"
-                    ))
-                    .into_owned(),
+    // overriding Gradle properties
+    if loader_version.is_some() || gradle_properties.is_some() {
+        log::step!("overriding Gradle properties");
+        modify_file(ACTIVE_DIR.join("gradle.properties"), |mut str| {
+            for (key, value) in loader_version
+                .map(|version| ("loader_version", version))
+                .into_iter()
+                .chain(
+                    gradle_properties
+                        .into_iter()
+                        .flatten()
+                        .map(|(key, value)| (key.as_str(), value)),
                 )
-            })?;
-        }
-
-        // run
-        println!("\x1b[36m>> running extraction\x1b[0m");
-        let is_terminal = std::io::stdout().lock().is_terminal();
-        let mut stdout_log = vec![];
-        let mut cmd = Command::new(ACTIVE_DIR.join("gradlew"))
-            .arg(if run_client { "runClient" } else { "runServer" })
-            .current_dir(&*ACTIVE_DIR)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .with_context(|| "failed to run extraction for mod")?;
-        let stdout = cmd.stdout.take().unwrap();
-        let mut stderr_reader = cmd.stderr.take().unwrap();
-        let mut lines = BufReader::new(stdout).lines();
-        print!("\x1b[?7l"); // disable line wrapping
+            {
+                str = set_gradle_property(&str, key, value)?;
+            }
+            Ok(str)
+        })?;
+    }
+
+    // run
+    // the live output is only shown when progress is not silenced
+    let is_terminal = std::io::stdout().lock().is_terminal() && log::enabled(Level::Info);
+    let mut attempt = 0;
+    let GradleRun {
+        end,
+        stdout_log,
+        stderr,
+        phases,
+        peak_memory,
+    } = loop {
+        log::step!("running extraction");
+        let run = run_gradle(
+            run_client,
+            java_home,
+            is_terminal,
+            options.timeout,
+            options.sandbox.as_ref(),
+        )
+        .await?;
+        if attempt < options.gradle_retries && run.is_transient_failure() {
+            attempt += 1;
+            let delay = retry::backoff(attempt);
+            log::warning!(
+                "extraction failed with a transient error, retrying in {}s ({attempt}/{})",
+                delay.as_secs(),
+                options.gradle_retries,
+            );
+            tokio::time::sleep(delay).await;
+            continue;
+        }
+        break run;
+    };
+    for (phase, duration) in phases {
+        timer.record(phase, duration);
+    }
+    // keep the logs and generated files, so failures can be investigated without rerunning
+    let log_dir = match RunLog::create(sh, slug, mc_major, options.keep_logs)
+        .and_then(|run_log| run_log.save(sh, &stdout_log, &stderr).map(|_| run_log.dir))
+    {
+        Ok(dir) => Some(dir),
+        Err(err) => {
+            log::warning!("could not save logs: {err:#}");
+            None
+        }
+    };
+    let fail = |message: String| {
         if is_terminal {
-            // make space for output
-            print!("{}", "\n".repeat(TERMINAL_CHILD_STDOUT_LINE_COUNT));
+            // print full log on failure
+            println!(
+                "{}\n{}",
+                log::paint("1;31", "------ STDOUT ------"),
+                stdout_log.join("\n")
+            );
+            println!("{}\n{stderr}", log::paint("1;31", "------ STDERR ------"));
+        }
+        let diagnoses = diagnose::diagnose(&stdout_log, &stderr, settings_classes);
+        diagnose::print(&diagnoses);
+        if let Some(dir) = &log_dir {
+            log::warning!("full logs saved to '{}'", dir.display());
+        }
+        match diagnoses.first() {
+            Some(diagnosis) => anyhow!("{message} (likely cause: {})", diagnosis.problem),
+            None => anyhow!(message),
+        }
+    };
+    match end {
+        RunEnd::Exited(status) if !status.success() => {
+            return Err(fail(format!(
+                "extraction exited with non-0 exit code: {status}"
+            )));
+        }
+        RunEnd::TimedOut => {
+            return Err(fail(format!(
+                "extraction timed out after {} seconds",
+                options.timeout.unwrap_or_default().as_secs()
+            )));
         }
-        while let Some(line) = lines.next_line().await? {
+        RunEnd::Exited(_) => {}
+        RunEnd::Stopped => log::info!("stopped the client after the rules were written"),
+    }
+    if !sh.path_exists("run/rules.json") {
+        return Err(fail("no output rules.json found".to_owned()));
+    }
+
+    let mut rules = serde_json::from_str::<Vec<RawRule>>(&sh.read_file("run/rules.json")?)?;
+    rules.sort_by_key(|rule| rule.name.clone());
+    if rules.is_empty() {
+        return Err(fail("extracted rules list is empty".to_owned()));
+    }
+    // custom printers might not print the loggers
+    let mut loggers = match sh.path_exists("run/loggers.json") {
+        true => serde_json::from_str::<Vec<RawLogger>>(&sh.read_file("run/loggers.json")?)?,
+        false => vec![],
+    };
+    // Carpet's own loggers are registered with every mod, but only belong to Carpet itself
+    loggers.retain(|logger| !logger.carpet || slug == CARPET_SLUG);
+    loggers.sort_by_key(|logger| logger.name.clone());
+    // Carpet always has loggers, so none means the logger registry could not be read
+    if slug == CARPET_SLUG && loggers.is_empty() {
+        return Err(fail("extracted loggers list is empty".to_owned()));
+    }
+
+    // save final json to file
+    log::step!("saving output");
+    timer.start(Phase::Save);
+    let output = RulesJson {
+        hash,
+        dependencies: resolved_deps,
+        rules,
+        loggers,
+    };
+    sh.write_file(output_data_file, serde_json::to_string(&output)?)?;
+    outputs.push(Output {
+        mod_name: name.clone(),
+        mod_slug: slug.clone(),
+        mod_url: mod_url.to_owned(),
+        minecraft_version: *mc_major,
+        version_url,
+        rules: output.rules,
+        loggers: output.loggers,
+    });
+
+    let metrics = timer.finish(slug, *mc_major, peak_memory);
+    for PhaseTiming { phase, secs } in &metrics.phases {
+        log::debug!("{phase} took {}", metrics::format_duration(*secs));
+    }
+    if let Err(err) = metrics.write(sh) {
+        log::warning!("could not save metrics: {err:#}");
+    }
+    Ok(Status::Succeeded)
+}
+
+/// Sets the value of a property in a `gradle.properties` file, adding it if it does not exist.
+///
+/// Line breaks and trailing backslashes, which would continue the line, are rejected, so a value
+/// cannot add other properties.
+fn set_gradle_property(properties: &str, key: &str, value: &str) -> Result<String> {
+    if key.contains(['\n', '\r', '=']) || key.ends_with('\\') {
+        bail!("invalid Gradle property name {key:?}");
+    }
+    if value.contains(['\n', '\r']) || value.ends_with('\\') {
+        bail!("invalid value for Gradle property `{key}`: {value:?}");
+    }
+    let mut found = false;
+    let mut lines = properties
+        .lines()
+        .map(|line| match line.split_once('=') {
+            Some((existing, _)) if existing.trim() == key => {
+                found = true;
+                format!("{key}={value}")
+            }
+            _ => line.to_owned(),
+        })
+        .collect_vec();
+    if !found {
+        lines.push(format!("{key}={value}"));
+    }
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Quotes a string for use in a Groovy build script.
+fn groovy_string(str: &str) -> String {
+    format!(
+        "'{}'",
+        str.replace('\\', "\\\\")
+            .replace('\'', "\\'")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r")
+    )
+}
+
+/// How a Gradle run ended.
+enum RunEnd {
+    Exited(ExitStatus),
+    TimedOut,
+    /// the client did not exit by itself after writing the rules and was stopped
+    Stopped,
+}
+
+struct GradleRun {
+    end: RunEnd,
+    stdout_log: Vec<String>,
+    stderr: String,
+    /// the duration of the Gradle configure, server boot, and printer phases that were reached
+    phases: Vec<(Phase, Duration)>,
+    peak_memory: Option<u64>,
+}
+
+impl GradleRun {
+    /// Combines this run with one that was started after it finished.
+    fn then(self, next: GradleRun) -> GradleRun {
+        let mut phases = self.phases;
+        for (phase, duration) in next.phases {
+            match phases.iter_mut().find(|(existing, _)| *existing == phase) {
+                Some((_, total)) => *total += duration,
+                None => phases.push((phase, duration)),
+            }
+        }
+        GradleRun {
+            end: next.end,
+            stdout_log: [self.stdout_log, next.stdout_log].concat(),
+            stderr: self.stderr + &next.stderr,
+            phases,
+            peak_memory: self.peak_memory.max(next.peak_memory),
+        }
+    }
+
+    /// Returns whether the run failed with an error that will likely go away when retrying, like
+    /// network issues while resolving dependencies.
+    fn is_transient_failure(&self) -> bool {
+        matches!(self.end, RunEnd::Exited(status) if !status.success())
+            && self
+                .stdout_log
+                .iter()
+                .map(String::as_str)
+                .chain([self.stderr.as_str()])
+                .any(|text| {
+                    regex_is_match!(
+                        r"(?i)connection reset|timed out|received status code 5\d\d|remote host terminated the handshake|premature end of content-length",
+                        text
+                    )
+                })
+    }
+}
+
+async fn run_gradle(
+    run_client: bool,
+    java_home: &Path,
+    is_terminal: bool,
+    timeout: Option<Duration>,
+    sandbox: Option<&Sandbox>,
+) -> Result<GradleRun> {
+    // without a daemon, all Gradle and Minecraft processes stay in the child's process group
+    let gradlew = ACTIVE_DIR.join("gradlew");
+    let start = Instant::now();
+    // the sandbox has no network, so dependencies are resolved and everything is compiled before
+    let prepared = match sandbox {
+        Some(_) => {
+            log::info!("resolving dependencies before entering the sandbox");
+            let mut gradle = Command::new(&gradlew);
+            gradle.args(["--no-daemon", "classes"]);
+            if run_client {
+                gradle.arg("downloadAssets");
+            }
+            let prepared = monitor_gradle(gradle, java_home, false, is_terminal, timeout).await?;
+            if !matches!(prepared.end, RunEnd::Exited(status) if status.success()) {
+                return Ok(prepared);
+            }
+            Some(prepared)
+        }
+        None => None,
+    };
+
+    let mut gradle = if run_client && !process::has_display() {
+        let xvfb_run = process::find_executable("xvfb-run").with_context(|| {
+            "running the client requires a display, but none is available and `xvfb-run` was not \
+             found, install Xvfb to run the client on a virtual display"
+        })?;
+        log::info!("no display available, running the client on a virtual display");
+        let mut cmd = Command::new(xvfb_run);
+        cmd.arg("--auto-servernum")
+            .arg("--server-args=-screen 0 1280x720x24")
+            .arg(gradlew)
+            // there is no GPU in the virtual display
+            .env("LIBGL_ALWAYS_SOFTWARE", "1");
+        cmd
+    } else {
+        Command::new(gradlew)
+    };
+    gradle
+        .arg("--no-daemon")
+        .arg(if run_client { "runClient" } else { "runServer" });
+    if let Some(sandbox) = sandbox {
+        log::info!("running in the sandbox");
+        gradle.arg("--offline").current_dir(&*ACTIVE_DIR);
+        gradle = sandbox.wrap(gradle)?;
+    }
+    let timeout = timeout.map(|timeout| timeout.saturating_sub(start.elapsed()));
+    let run = monitor_gradle(gradle, java_home, run_client, is_terminal, timeout).await?;
+    Ok(match prepared {
+        Some(prepared) => prepared.then(run),
+        None => run,
+    })
+}
+
+/// Runs a Gradle command in the active mod dir, shows its output, and measures its phases.
+async fn monitor_gradle(
+    mut gradle: Command,
+    java_home: &Path,
+    run_client: bool,
+    is_terminal: bool,
+    timeout: Option<Duration>,
+) -> Result<GradleRun> {
+    let mut stdout_log = vec![];
+    gradle
+        .current_dir(&*ACTIVE_DIR)
+        .env("JAVA_HOME", java_home)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    log::debug!("running {gradle:?}");
+    let (mut cmd, group) =
+        process::spawn_group(gradle).with_context(|| "failed to run extraction for mod")?;
+    let stdout = cmd.stdout.take().unwrap();
+    let mut stderr_reader = cmd.stderr.take().unwrap();
+    // read stderr concurrently, so the child never blocks on a full pipe
+    let stderr_task = tokio::spawn(async move {
+        let mut stderr = String::new();
+        stderr_reader
+            .read_to_string(&mut stderr)
+            .await
+            .map(|_| stderr)
+    });
+    let mut lines = BufReader::new(stdout).lines();
+    let no_line_wrap = is_terminal.then(NoLineWrap::new);
+    if is_terminal {
+        // make space for output
+        print!("{}", "\n".repeat(TERMINAL_CHILD_STDOUT_LINE_COUNT));
+    }
+    let start = Instant::now();
+    let mut run_task_start = None;
+    let mut printer_start = None;
+    let mut peak_memory = None;
+    let mut rules_written = None;
+    let mut poll_interval = tokio::time::interval(POLL_INTERVAL);
+    let run = async {
+        loop {
+            let line = tokio::select! {
+                line = lines.next_line() => match line? {
+                    Some(line) => line,
+                    None => break,
+                },
+                _ = poll_interval.tick() => {
+                    peak_memory = peak_memory.max(group.memory_usage());
+                    // the client does not always exit by itself after the printer is done
+                    if run_client {
+                        match rules_written {
+                            None if ACTIVE_DIR.join("run/rules.json").is_file() => {
+                                rules_written = Some(Instant::now());
+                            }
+                            Some(written) if written.elapsed() > CLIENT_QUIT_TIMEOUT => {
+                                return Ok(RunEnd::Stopped);
+                            }
+                            _ => {}
+                        }
+                    }
+                    continue;
+                }
+            };
+            if run_task_start.is_none() && regex_is_match!(r"^> Task :run(Server|Client)", &line) {
+                run_task_start = Some(Instant::now());
+            }
+            if printer_start.is_none() && line.contains(PRINTER_START_MARKER) {
+                printer_start = Some(Instant::now());
+            }
             if is_terminal {
                 // print last n lines of output if in terminal
                 print!("\x1b[{TERMINAL_CHILD_STDOUT_LINE_COUNT}A\r\x1b[0J");
@@ -576,67 +1177,57 @@ dependencies {{
                         "\n".repeat(TERMINAL_CHILD_STDOUT_LINE_COUNT - stdout_log.len())
                     );
                 }
-            } else {
+            } else if log::enabled(Level::Info) {
                 println!("{line}");
             }
             stdout_log.push(line);
         }
-        print!("\x1b[?7h"); // enable line wrapping again
-        let mut stderr = String::new();
-        stderr_reader.read_to_string(&mut stderr).await?;
-        if is_terminal {
-            // move back up
-            print!("\x1b[{TERMINAL_CHILD_STDOUT_LINE_COUNT}A\r\x1b[0J");
-        } else {
-            println!("\x1b[1;33m------ STDERR ------\x1b[0m\n{stderr}");
-        }
-        let status = cmd.wait().await?;
-        let on_err = || {
-            if is_terminal {
-                // print full log on failure
-                println!(
-                    "\x1b[1;31m------ STDOUT ------\x1b[0m\n{}",
-                    stdout_log.join("\n")
-                );
-                println!("\x1b[1;31m------ STDERR ------\x1b[0m\n{stderr}");
-            }
-        };
-        if !status.success() {
-            on_err();
-            bail!("extraction exited with non-0 exit code: {status}");
-        }
-        if !sh.path_exists("run/rules.json") {
-            on_err();
-            bail!("no output rules.json found");
-        }
-
-        let mut rules = serde_json::from_str::<Vec<RawRule>>(&sh.read_file("run/rules.json")?)?;
-        rules.sort_by_key(|rule| rule.name.clone());
-        if rules.is_empty() {
-            on_err();
-            bail!("extracted rules list is empty");
-        }
-
-        // save final json to file
-        println!("\x1b[36m>> saving output\x1b[0m");
-        let output = RulesJson { hash, rules };
-        sh.write_file(output_data_file, serde_json::to_string(&output)?)?;
-        outputs.push(Output {
-            mod_name: name.clone(),
-            mod_slug: slug.clone(),
-            mod_url: mod_url.clone(),
-            minecraft_version: *mc_major,
-            version_url,
-            rules: output.rules,
-        });
+        anyhow::Ok(RunEnd::Exited(cmd.wait().await?))
+    };
+    let result = match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, run)
+            .await
+            .unwrap_or(Ok(RunEnd::TimedOut)),
+        None => run.await,
+    };
+    let end = Instant::now();
+    // make sure no processes are left over, this also closes stderr after a timeout
+    group.kill();
+    drop(no_line_wrap);
+    let stderr = stderr_task.await??;
+    if is_terminal {
+        // move back up
+        print!("\x1b[{TERMINAL_CHILD_STDOUT_LINE_COUNT}A\r\x1b[0J");
+    } else if log::enabled(Level::Info) {
+        println!("{}\n{stderr}", log::paint("1;33", "------ STDERR ------"));
     }
-    Ok(())
+    let phases = [
+        (Phase::GradleConfigure, Some(start), run_task_start),
+        (Phase::ServerBoot, run_task_start, printer_start),
+        (Phase::Printer, printer_start, None),
+    ]
+    .into_iter()
+    .filter_map(|(phase, from, to)| Some((phase, to.unwrap_or(end) - from?)))
+    .collect();
+    Ok(GradleRun {
+        end: result?,
+        stdout_log,
+        stderr,
+        phases,
+        peak_memory,
+    })
+}
+
+fn data_file(slug: &str, mc_major: &MinecraftMajorVersion) -> PathBuf {
+    DATA_DIR.join(format!("{slug}-{mc_major}.json"))
 }
 
-fn hash(state: impl Hash) -> u64 {
-    let mut hasher = DefaultHasher::new();
-    state.hash(&mut hasher);
-    hasher.finish()
+/// Reads the existing data for a mod version, if it exists and is non-empty.
+fn read_data(sh: &Shell, slug: &str, mc_major: &MinecraftMajorVersion) -> Option<RulesJson> {
+    sh.read_file(data_file(slug, mc_major))
+        .ok()
+        .and_then(|str| serde_json::from_str::<RulesJson>(&str).ok())
+        .filter(|output| !output.rules.is_empty())
 }
 
 async fn get_modrinth_dep(
@@ -653,15 +1244,11 @@ async fn get_modrinth_dep(
     // "CarpetTCTCAddition-1.14.4-2.2.201+8009659-stable.jar" from the maven when the primary file
     // is called "CarpetTCTCAddition-all-2.2.201+8009659-stable.jar"
     let url = format!("https://api.modrinth.com/maven/maven/modrinth/{slug}/{version}/{filename}");
-    println!("\x1b[34m> downloading jar from '{url}'\x1b[0m");
-    let res = CLIENT.get(url).send().await?;
-    if !res.status().is_success() {
-        bail!(
-            "could not download jar: Modrinth responded with status code {}",
-            res.status()
-        );
+    log::info!("downloading jar from '{url}'");
+    let (status, bytes) = retry::get(&url).await?;
+    if !status.is_success() {
+        bail!("could not download jar: Modrinth responded with status code {status}");
     }
-    let bytes = res.bytes().await?;
     sh.write_file("libs/mod.jar", bytes)?;
 
     Ok("files('libs/mod.jar')".into())
@@ -670,22 +1257,56 @@ async fn get_modrinth_dep(
 async fn get_github_dep(sh: &Shell, repo: &str, tag: &str, asset: &str) -> Result<String> {
     // download jar
     let url = format!("https://github.com/{repo}/releases/download/{tag}/{asset}");
-    println!("\x1b[34m> downloading jar from '{url}'\x1b[0m");
-    let res = CLIENT.get(url).send().await?;
-    if !res.status().is_success() {
-        bail!(
-            "could not download jar: GitHub responded with status code {}",
-            res.status()
-        );
+    log::info!("downloading jar from '{url}'");
+    let (status, bytes) = retry::get(&url).await?;
+    if !status.is_success() {
+        bail!("could not download jar: GitHub responded with status code {status}");
     }
-    let bytes = res.bytes().await?;
     sh.write_file("libs/mod.jar", bytes)?;
 
     Ok("files('libs/mod.jar')".into())
 }
 
-fn combine(sh: &Shell, outputs: Vec<Output>) -> Result<()> {
+/// Returns the mod jar, which is downloaded again if it is not already part of the build.
+async fn get_mod_jar(
+    sh: &Shell,
+    slug: &str,
+    project_id: i32,
+    source: &VersionSource,
+) -> Result<Vec<u8>> {
+    if sh.path_exists("libs/mod.jar") {
+        return Ok(sh.read_binary_file("libs/mod.jar")?);
+    }
+    let url = match source {
+        VersionSource::Modrinth { version, .. } => deps::modrinth_file_url(slug, version).await?,
+        VersionSource::CurseForge { file_id } => format!(
+            "https://cursemaven.com/curse/maven/{slug}-{project_id}/{file_id}/{slug}-{project_id}-{file_id}.jar"
+        ),
+        VersionSource::GitHub { .. } => bail!("the GitHub jar was not downloaded"),
+    };
+    log::info!("downloading jar from '{url}'");
+    let (status, bytes) = retry::get(&url).await?;
+    if !status.is_success() {
+        bail!("could not download jar: server responded with status code {status}");
+    }
+    Ok(bytes)
+}
+
+fn combine(sh: &Shell, outputs: Vec<Output>, summary: &Summary, reporter: &Reporter) -> Result<()> {
     let mut combined: CombinedJson = vec![];
+    let mut combined_loggers: CombinedLoggersJson = vec![];
+
+    // metrics of the last extraction of every included mod version, which may be missing when
+    // the data was extracted elsewhere
+    let mut metrics_by_mod: BTreeMap<String, Vec<ExtractionMetrics>> = BTreeMap::new();
+    for output in &outputs {
+        if let Some(metrics) = metrics::read(sh, &output.mod_slug, &output.minecraft_version) {
+            metrics_by_mod
+                .entry(output.mod_name.clone())
+                .or_default()
+                .push(metrics);
+        }
+    }
 
     for Output {
         mod_name,
@@ -694,8 +1315,35 @@ fn combine(sh: &Shell, outputs: Vec<Output>) -> Result<()> {
         minecraft_version,
         version_url,
         rules,
+        loggers,
     } in outputs
     {
+        for logger in loggers {
+            match combined_loggers.iter_mut().find(|other| {
+                other.name == logger.name
+                    && other.default == logger.default
+                    && other.options == logger.options
+                    && other.hud == logger.hud
+                    && other.mod_slug == mod_slug
+            }) {
+                Some(other) => {
+                    other.minecraft_versions.push(minecraft_version);
+                    other.version_urls.push(version_url.clone());
+                }
+                None => combined_loggers.push(Logger {
+                    name: logger.name,
+                    default: logger.default,
+                    options: logger.options,
+                    hud: logger.hud,
+                    mod_name: mod_name.clone(),
+                    mod_slug: mod_slug.clone(),
+                    mod_url: mod_url.clone(),
+                    minecraft_versions: vec![minecraft_version],
+                    version_urls: vec![version_url.clone()],
+                }),
+            }
+        }
+
         for rule in rules {
             let new_rule = Rule {
                 name: rule.name,
@@ -708,6 +1356,7 @@ fn combine(sh: &Shell, outputs: Vec<Output>) -> Result<()> {
                 extras: rule.extras,
                 validators: rule.validators,
                 config_files: rule.config_files,
+                translations: rule.translations,
                 mod_name: mod_name.clone(),
                 mod_slug: mod_slug.clone(),
                 mod_url: mod_url.clone(),
@@ -738,6 +1387,8 @@ fn combine(sh: &Shell, outputs: Vec<Output>) -> Result<()> {
                 {
                     rule.description = new_rule.description.clone();
                     rule.validators = new_rule.validators.clone();
+                    // newer translations win, but languages only older versions had are kept
+                    rule.translations.extend(new_rule.translations.clone());
                     rule.minecraft_versions.push(minecraft_version);
                     rule.version_urls.push(version_url.clone());
                     did_modify = true;
@@ -749,12 +1400,26 @@ fn combine(sh: &Shell, outputs: Vec<Output>) -> Result<()> {
         }
     }
     combined.sort_by_key(|rule| rule.name.clone());
+    combined_loggers.sort_by_key(|logger| logger.name.clone());
 
-    // write file
+    // write files
     sh.write_file(
         DATA_DIR.join("combined.json"),
         serde_json::to_string(&combined)?,
     )?;
+    sh.write_file(
+        DATA_DIR.join("loggers.json"),
+        serde_json::to_string(&combined_loggers)?,
+    )?;
+
+    // write a report of what happened to outdated data
+    let report = CombineReport {
+        stale: summary.stale_entries(),
+    };
+    sh.write_file(
+        WORKSPACE_DIR.join("combine-report.json"),
+        serde_json::to_string_pretty(&report)?,
+    )?;
 
     // get and print stats
     let mut count_by_mod: BTreeMap<String, HashSet<String>> = BTreeMap::new();
@@ -782,41 +1447,156 @@ fn combine(sh: &Shell, outputs: Vec<Output>) -> Result<()> {
         .into_iter()
         .map(|(name, set)| (name, set.len()))
         .collect_vec();
-    count_by_mod.sort_by(|a, b| b.1.cmp(&a.1));
+    count_by_mod.sort_by_key(|(_, count)| Reverse(*count));
     let mut count_by_version = count_by_version
         .into_iter()
         .map(|(name, set)| (name, set.len()))
         .collect_vec();
-    count_by_version.sort_by(|a, b| b.1.cmp(&a.1));
+    count_by_version.sort_by_key(|(_, count)| Reverse(*count));
     let mut count_by_category = count_by_category
         .into_iter()
         .map(|(name, set)| (name, set.len()))
         .collect_vec();
-    count_by_category.sort_by(|a, b| b.1.cmp(&a.1));
+    count_by_category.sort_by_key(|(_, count)| Reverse(*count));
     let total_count: usize = count_by_mod.iter().map(|(_, count)| count).sum();
 
-    println!("\x1b[1;32m>>> Rules parsed: {total_count}\x1b[0m");
-    println!("\x1b[32m>> Count per mod:\x1b[0m");
+    log::event(
+        Level::Info,
+        "1;32",
+        ">>> ",
+        format_args!("Rules parsed: {total_count}"),
+    );
+    log::event(Level::Info, "32", ">> ", format_args!("Count per mod:"));
     let mut stats_md = format!("**Rules parsed**: {total_count}\n\n");
     stats_md += "Count per mod:\n\n";
     for (mod_name, count) in &count_by_mod {
-        println!("\x1b[32m> {mod_name}: {count}\x1b[0m");
+        let extraction = metrics_by_mod
+            .get(mod_name)
+            .map(|metrics| {
+                let secs = metrics.iter().map(ExtractionMetrics::total_secs).sum();
+                let peak_memory = metrics.iter().filter_map(|m| m.peak_memory_bytes).max();
+                format!(
+                    " (extracted in {}{})",
+                    metrics::format_duration(secs),
+                    peak_memory
+                        .map(|bytes| format!(", peak memory {}", util::format_size(bytes)))
+                        .unwrap_or_default()
+                )
+            })
+            .unwrap_or_default();
+        log::event(
+            Level::Info,
+            "32",
+            "> ",
+            format_args!("{mod_name}: {count}{extraction}"),
+        );
         stats_md += &format!("- **{mod_name}**: {count}\n");
     }
-    if let Ok(path) = env::var("GITHUB_OUTPUT") {
-        sh.write_file(path, format!("stats<<EOF\n{stats_md}EOF"))?;
+    if !report.stale.is_empty() {
+        log::event(Level::Warn, "33", ">> ", format_args!("Outdated data:"));
+        stats_md += "\nOutdated data:\n\n";
+        for StaleEntry {
+            mod_slug,
+            minecraft_version,
+            decision,
+        } in &report.stale
+        {
+            let decision = match decision {
+                StaleDecision::Fallback => "using last good data",
+                StaleDecision::Dropped => "dropped",
+            };
+            log::warning!("{mod_slug} for {minecraft_version}: {decision}");
+            stats_md += &format!("- `{mod_slug}` for {minecraft_version}: {decision}\n");
+        }
     }
-    println!("\x1b[32m>> Count per version:\x1b[0m");
+    let short_stats_md = stats_md.clone();
+    log::event(Level::Info, "32", ">> ", format_args!("Count per version:"));
     stats_md += "\nCount per version:\n\n";
     for (version, count) in &count_by_version {
-        println!("\x1b[32m> {version}: {count}\x1b[0m");
+        log::event(Level::Info, "32", "> ", format_args!("{version}: {count}"));
         stats_md += &format!("- **{version}**: {count}\n");
     }
     stats_md += "\nCount per category:\n\n";
     for (category, count) in &count_by_category {
         stats_md += &format!("- `{category}`: {count}\n");
     }
-    sh.write_file(WORKSPACE_DIR.join("stats.md"), stats_md)?;
+    sh.write_file(WORKSPACE_DIR.join("stats.md"), &stats_md)?;
+
+    // timings change with every run, so they are only part of the reports, not `stats.md`
+    let metrics = metrics_by_mod.into_values().flatten().collect_vec();
+    if !metrics.is_empty() {
+        stats_md += "\nExtractions:\n\n| Mod | Minecraft | Duration | Peak memory |\n| --- | --- | --- | --- |\n";
+        for metrics in &metrics {
+            stats_md += &format!(
+                "| `{}` | {} | {} | {} |\n",
+                metrics.mod_slug,
+                metrics.minecraft_version,
+                metrics::format_duration(metrics.total_secs()),
+                metrics
+                    .peak_memory_bytes
+                    .map(util::format_size)
+                    .unwrap_or_else(|| "-".to_owned())
+            );
+        }
+    }
+
+    reporter.stats(&Stats {
+        total: total_count,
+        by_mod: count_by_mod.into_iter().map(Count::from).collect(),
+        by_version: count_by_version.into_iter().map(Count::from).collect(),
+        by_category: count_by_category.into_iter().map(Count::from).collect(),
+        stale: report.stale,
+        metrics,
+        short_markdown: short_stats_md,
+        markdown: stats_md,
+    })?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROPERTIES: &str = "org.gradle.jvmargs=-Xmx1G\nloader_version = 0.14.21\n";
+
+    #[test]
+    fn set_gradle_property_replaces_existing_key() {
+        assert_eq!(
+            set_gradle_property(PROPERTIES, "loader_version", "0.15.11").unwrap(),
+            "org.gradle.jvmargs=-Xmx1G\nloader_version=0.15.11\n"
+        );
+    }
+
+    #[test]
+    fn set_gradle_property_appends_missing_key() {
+        assert_eq!(
+            set_gradle_property(PROPERTIES, "loom_version", "1.6-SNAPSHOT").unwrap(),
+            "org.gradle.jvmargs=-Xmx1G\nloader_version = 0.14.21\nloom_version=1.6-SNAPSHOT\n"
+        );
+    }
+
+    #[test]
+    fn set_gradle_property_rejects_line_breaks() {
+        for (key, value) in [
+            ("loader_version", "0.15.11\nloom_version=0.1"),
+            ("loader_version", "0.15.11\rloom_version=0.1"),
+            ("loader_version", "0.15.11\\"),
+            ("loader_version\nloom_version", "0.1"),
+            ("loader_version=0.15.11", "0.1"),
+        ] {
+            assert!(
+                set_gradle_property(PROPERTIES, key, value).is_err(),
+                "{key:?}={value:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn groovy_string_quotes() {
+        assert_eq!(groovy_string("-Xmx4G"), "'-Xmx4G'");
+        assert_eq!(groovy_string("it's"), "'it\\'s'");
+        assert_eq!(groovy_string("C:\\dir\\"), "'C:\\\\dir\\\\'");
+        assert_eq!(groovy_string("a\nb\r"), "'a\\nb\\r'");
+    }
+}
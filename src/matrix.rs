@@ -0,0 +1,39 @@
+use anyhow::Result;
+use serde::Serialize;
+use xshell::Shell;
+
+use crate::{
+    read_data,
+    report::Reporter,
+    resolve::ResolvedVersion,
+    schema::{MinecraftMajorVersion, Mod},
+};
+
+#[derive(Serialize)]
+pub struct MatrixEntry<'a> {
+    pub slug: &'a str,
+    pub minecraft_version: MinecraftMajorVersion,
+}
+
+/// Builds a CI job matrix with one entry per mod and major Minecraft version.
+pub fn get_matrix(sh: &Shell, mods: &[Mod], stale_only: bool, reporter: &Reporter) -> Result<()> {
+    let mut entries = vec![];
+    for mod_ in mods {
+        for (mc_major, version) in &mod_.versions {
+            if stale_only {
+                let hash = ResolvedVersion::new(mod_, *mc_major, version)?.cache_key();
+                if read_data(sh, &mod_.slug, mc_major).is_some_and(|data| data.hash == hash) {
+                    continue;
+                }
+            }
+            entries.push(MatrixEntry {
+                slug: &mod_.slug,
+                minecraft_version: *mc_major,
+            });
+        }
+    }
+
+    reporter.matrix(&entries)?;
+
+    Ok(())
+}
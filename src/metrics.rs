@@ -0,0 +1,133 @@
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use xshell::Shell;
+
+use crate::{schema::MinecraftMajorVersion, METRICS_DIR};
+
+/// A timed phase of an extraction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "lowercase")]
+pub enum Phase {
+    /// copying the template mod
+    #[strum(serialize = "template copy")]
+    TemplateCopy,
+    /// downloading the mod jar
+    Download,
+    /// everything Gradle does before the run task, including resolving dependencies and
+    /// compiling
+    #[strum(serialize = "gradle configure")]
+    GradleConfigure,
+    /// starting Minecraft until the printer is called
+    #[strum(serialize = "server boot")]
+    ServerBoot,
+    /// printing the rules until Minecraft exits
+    Printer,
+    /// saving the output
+    Save,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhaseTiming {
+    pub phase: Phase,
+    pub secs: f64,
+}
+
+/// The metrics of one extraction, written to `metrics/{slug}-{major}.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractionMetrics {
+    pub mod_slug: String,
+    pub minecraft_version: MinecraftMajorVersion,
+    pub phases: Vec<PhaseTiming>,
+    /// the highest combined resident memory of Gradle and Minecraft, if it could be measured
+    pub peak_memory_bytes: Option<u64>,
+}
+
+impl ExtractionMetrics {
+    pub fn total_secs(&self) -> f64 {
+        self.phases.iter().map(|timing| timing.secs).sum()
+    }
+
+    pub fn write(&self, sh: &Shell) -> Result<()> {
+        sh.write_file(
+            metrics_file(&self.mod_slug, &self.minecraft_version),
+            serde_json::to_string_pretty(self)?,
+        )?;
+        Ok(())
+    }
+}
+
+/// Measures consecutive phases.
+pub struct PhaseTimer {
+    current: Option<(Phase, Instant)>,
+    phases: Vec<PhaseTiming>,
+}
+
+impl PhaseTimer {
+    pub fn new() -> Self {
+        Self {
+            current: None,
+            phases: vec![],
+        }
+    }
+
+    /// Ends the current phase, if any, and starts the given one.
+    pub fn start(&mut self, phase: Phase) {
+        self.end();
+        self.current = Some((phase, Instant::now()));
+    }
+
+    /// Ends the current phase, if any.
+    pub fn end(&mut self) {
+        if let Some((phase, start)) = self.current.take() {
+            self.record(phase, start.elapsed());
+        }
+    }
+
+    /// Records a phase that was measured elsewhere.
+    pub fn record(&mut self, phase: Phase, duration: Duration) {
+        self.phases.push(PhaseTiming {
+            phase,
+            secs: duration.as_secs_f64(),
+        });
+    }
+
+    pub fn finish(
+        mut self,
+        slug: &str,
+        mc_major: MinecraftMajorVersion,
+        peak_memory_bytes: Option<u64>,
+    ) -> ExtractionMetrics {
+        self.end();
+        ExtractionMetrics {
+            mod_slug: slug.to_owned(),
+            minecraft_version: mc_major,
+            phases: self.phases,
+            peak_memory_bytes,
+        }
+    }
+}
+
+fn metrics_file(slug: &str, mc_major: &MinecraftMajorVersion) -> PathBuf {
+    METRICS_DIR.join(format!("{slug}-{mc_major}.json"))
+}
+
+/// Reads the metrics of the last extraction of a mod version, if there are any.
+pub fn read(sh: &Shell, slug: &str, mc_major: &MinecraftMajorVersion) -> Option<ExtractionMetrics> {
+    sh.read_file(metrics_file(slug, mc_major))
+        .ok()
+        .and_then(|str| serde_json::from_str(&str).ok())
+}
+
+pub fn format_duration(secs: f64) -> String {
+    let secs = secs.round() as u64;
+    match secs {
+        0..=59 => format!("{secs}s"),
+        _ => format!("{}m {:02}s", secs / 60, secs % 60),
+    }
+}
@@ -0,0 +1,106 @@
+use std::{
+    env,
+    io::{self, Write},
+    path::PathBuf,
+    process,
+};
+
+use tokio::process::{Child, Command};
+
+/// Spawns the command as the leader of a new process group, so that it and all its descendants
+/// can be killed at once with [`ProcessGroup::kill`].
+pub fn spawn_group(mut cmd: process::Command) -> io::Result<(Child, ProcessGroup)> {
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+    let child = Command::from(cmd).spawn()?;
+    let group = ProcessGroup(child.id());
+    Ok((child, group))
+}
+
+/// The process group of a spawned child, which is killed when this is dropped.
+///
+/// This makes sure that no Gradle or Minecraft processes are left behind, even when the
+/// extraction is cancelled by a timeout or Ctrl-C.
+pub struct ProcessGroup(Option<u32>);
+
+impl ProcessGroup {
+    pub fn kill(&self) {
+        #[cfg(unix)]
+        if let Some(pid) = self.0 {
+            // SAFETY: `killpg` has no memory safety requirements. A failure only means that
+            // the process group is already gone.
+            unsafe {
+                libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+            }
+        }
+    }
+
+    /// Returns the combined resident memory of all processes in the group in bytes.
+    ///
+    /// This is only supported on Linux, where it is read from `/proc`.
+    pub fn memory_usage(&self) -> Option<u64> {
+        #[cfg(target_os = "linux")]
+        if let Some(pgid) = self.0 {
+            // SAFETY: `sysconf` has no memory safety requirements
+            let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as u64;
+            let mut pages = 0;
+            for entry in std::fs::read_dir("/proc").ok()?.flatten() {
+                // processes may exit at any time, so unreadable entries are skipped
+                let Ok(stat) = std::fs::read_to_string(entry.path().join("stat")) else {
+                    continue;
+                };
+                // the fields after the command name, which itself may contain spaces
+                let Some((_, fields)) = stat.rsplit_once(')') else {
+                    continue;
+                };
+                let fields = fields.split_whitespace().collect::<Vec<_>>();
+                // `pgrp` and `rss` are the 5th and 24th field of the whole line
+                if fields.get(2).and_then(|pgrp| pgrp.parse::<u32>().ok()) == Some(pgid) {
+                    pages += fields
+                        .get(21)
+                        .and_then(|rss| rss.parse::<u64>().ok())
+                        .unwrap_or_default();
+                }
+            }
+            return Some(pages * page_size);
+        }
+        None
+    }
+}
+
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
+
+/// Returns the path of an executable on the `PATH`.
+pub fn find_executable(name: &str) -> Option<PathBuf> {
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Returns whether graphical programs can be shown, which is always assumed outside of Linux.
+pub fn has_display() -> bool {
+    !cfg!(target_os = "linux")
+        || env::var_os("DISPLAY").is_some_and(|display| !display.is_empty())
+        || env::var_os("WAYLAND_DISPLAY").is_some_and(|display| !display.is_empty())
+}
+
+/// Disables line wrapping in the terminal until this is dropped.
+pub struct NoLineWrap;
+
+impl NoLineWrap {
+    pub fn new() -> Self {
+        print!("\x1b[?7l");
+        Self
+    }
+}
+
+impl Drop for NoLineWrap {
+    fn drop(&mut self) {
+        print!("\x1b[?7h");
+        let _ = io::stdout().flush();
+    }
+}
@@ -0,0 +1,139 @@
+//! Quilt template mods, which are converted from the generated Fabric ones.
+
+use anyhow::{bail, Context, Result};
+use fs_extra::dir::CopyOptions;
+use lazy_regex::{regex_captures, regex_replace};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use xshell::Shell;
+
+use crate::{
+    log, modify_file, retry,
+    schema::{Loader, MinecraftVersion},
+    set_gradle_property, TEMPLATES_DIR,
+};
+
+const QUILT_MAVEN: &str = "https://maven.quiltmc.org/repository/release/";
+const LOADER_VERSIONS_URL: &str = "https://meta.quiltmc.org/v3/versions/loader";
+
+#[derive(Deserialize)]
+struct LoaderVersion {
+    version: String,
+}
+
+/// Generates the Quilt template mod for the Minecraft version from the Fabric one, unless it
+/// already exists.
+pub async fn gen_template(sh: &Shell, minecraft_version: MinecraftVersion) -> Result<()> {
+    let name = Loader::Quilt.template_name(minecraft_version);
+    let dir = TEMPLATES_DIR.join(&name);
+    log::info!("generating mod template for '{name}'");
+    if dir.join("gradlew").is_file() {
+        log::info!("directory already exists, skipping");
+        return Ok(());
+    }
+
+    let loader_version = latest_loader_version().await?;
+    let loom_version = latest_loom_version().await?;
+    log::info!("using Quilt Loader {loader_version} and Quilt Loom {loom_version}");
+
+    // convert a copy, so an interrupted conversion is not mistaken for a finished template
+    let partial_dir = TEMPLATES_DIR.join(format!("{name}.partial"));
+    sh.remove_path(&partial_dir)?;
+    let fabric_dir = TEMPLATES_DIR.join(Loader::Fabric.template_name(minecraft_version));
+    fs_extra::dir::copy(
+        &fabric_dir,
+        &partial_dir,
+        &CopyOptions::new().copy_inside(true),
+    )
+    .with_context(|| {
+        format!(
+            "couldn't copy template mod from '{}' to '{}'",
+            fabric_dir.display(),
+            partial_dir.display()
+        )
+    })?;
+
+    modify_file(partial_dir.join("settings.gradle"), |str| {
+        Ok(regex_replace!(r"repositories \{", &str, |whole| format!(
+            "{whole}\n\t\tmaven {{\n\t\t\tname = 'Quilt'\n\t\t\turl = '{QUILT_MAVEN}'\n\t\t}}"
+        ))
+        .into_owned())
+    })?;
+    modify_file(partial_dir.join("build.gradle"), |str| {
+        let str = regex_replace!(
+            r#"id ['"]fabric-loom['"] version ['"][^'"]+['"]"#,
+            &str,
+            |_| format!("id 'org.quiltmc.loom' version '{loom_version}'")
+        );
+        if !str.contains("org.quiltmc.loom") {
+            bail!("could not find the Loom plugin");
+        }
+        Ok(str
+            .replace("net.fabricmc:fabric-loader:", "org.quiltmc:quilt-loader:")
+            .replace("\"fabric.mod.json\"", "\"quilt.mod.json\"")
+            + &format!("\nrepositories {{\n\tmaven {{ url = '{QUILT_MAVEN}' }}\n}}\n"))
+    })?;
+    modify_file(partial_dir.join("gradle.properties"), |str| {
+        set_gradle_property(&str, "loader_version", &loader_version)
+    })?;
+
+    // Quilt can also load `fabric.mod.json`, but `quilt.mod.json` allows Quilt-only features
+    let resources = partial_dir.join("src/main/resources");
+    let fabric_conf = serde_json::from_str::<Map<String, Value>>(
+        &sh.read_file(resources.join("fabric.mod.json"))?,
+    )?;
+    let mut quilt_conf = json!({
+        "schema_version": 1,
+        "quilt_loader": {
+            "group": "com.example",
+            "id": fabric_conf["id"],
+            "version": fabric_conf["version"],
+            "metadata": { "name": fabric_conf["name"] },
+            "intermediate_mappings": "net.fabricmc:intermediary",
+            "entrypoints": {},
+            "depends": [],
+        },
+    });
+    if let Some(mixins) = fabric_conf.get("mixins") {
+        quilt_conf["mixin"] = mixins.clone();
+    }
+    if let Some(access_widener) = fabric_conf.get("accessWidener") {
+        quilt_conf["access_widener"] = access_widener.clone();
+    }
+    sh.write_file(
+        resources.join("quilt.mod.json"),
+        serde_json::to_string_pretty(&quilt_conf)?,
+    )?;
+    sh.remove_path(resources.join("fabric.mod.json"))?;
+
+    sh.remove_path(&dir)?;
+    std::fs::rename(&partial_dir, &dir)?;
+    Ok(())
+}
+
+async fn latest_loader_version() -> Result<String> {
+    let (status, bytes) = retry::get(LOADER_VERSIONS_URL).await?;
+    if !status.is_success() {
+        bail!(
+            "could not get Quilt Loader versions: Quilt meta responded with status code {status}"
+        );
+    }
+    serde_json::from_slice::<Vec<LoaderVersion>>(&bytes)?
+        .into_iter()
+        // pre-releases are marked with a suffix like `-beta.1`
+        .find(|version| !version.version.contains('-'))
+        .map(|version| version.version)
+        .context("no stable Quilt Loader version found")
+}
+
+async fn latest_loom_version() -> Result<String> {
+    let url = format!("{QUILT_MAVEN}org/quiltmc/loom/maven-metadata.xml");
+    let (status, bytes) = retry::get(&url).await?;
+    if !status.is_success() {
+        bail!("could not get Quilt Loom versions: Quilt maven responded with status code {status}");
+    }
+    let metadata = String::from_utf8(bytes)?;
+    let (_, version) = regex_captures!(r"<release>([^<]+)</release>", &metadata)
+        .context("no Quilt Loom release found")?;
+    Ok(version.to_owned())
+}
@@ -0,0 +1,220 @@
+use std::{
+    env,
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::{
+    cli::Args,
+    matrix::MatrixEntry,
+    metrics::ExtractionMetrics,
+    schema::{MinecraftMajorVersion, StaleEntry},
+    WORKSPACE_DIR,
+};
+
+/// Where and in which format job matrices and stats are reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum ReportFormat {
+    /// plain JSON printed to stdout
+    Stdout,
+    /// plain JSON files in the report dir
+    Json,
+    /// GitHub Actions step outputs in `GITHUB_OUTPUT`
+    GitHub,
+    /// a GitLab CI dotenv artifact in the report dir, with the job matrix in a JSON file next to
+    /// it, as it is too large for a dotenv variable
+    GitLab,
+    /// a Markdown job summary in `GITHUB_STEP_SUMMARY` or the report dir
+    Summary,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Count<T> {
+    pub name: T,
+    pub count: usize,
+}
+
+impl<T> From<(T, usize)> for Count<T> {
+    fn from((name, count): (T, usize)) -> Self {
+        Self { name, count }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Stats {
+    pub total: usize,
+    pub by_mod: Vec<Count<String>>,
+    pub by_version: Vec<Count<MinecraftMajorVersion>>,
+    pub by_category: Vec<Count<String>>,
+    pub stale: Vec<StaleEntry>,
+    /// the metrics of the last extraction of every included mod version, where available
+    pub metrics: Vec<ExtractionMetrics>,
+    /// Markdown with the total, the count per mod, and the outdated data, for notifications
+    #[serde(skip)]
+    pub short_markdown: String,
+    /// Markdown with all stats
+    #[serde(skip)]
+    pub markdown: String,
+}
+
+pub struct Reporter {
+    /// the formats selected with `--report`, or `None` to choose automatically
+    formats: Option<Vec<ReportFormat>>,
+    dir: PathBuf,
+}
+
+impl Reporter {
+    pub fn from_args(args: &Args) -> Result<Self> {
+        let formats = args
+            .value("report")
+            .map(|formats| {
+                formats
+                    .split(',')
+                    .map(|format| {
+                        format.parse().with_context(|| {
+                            format!(
+                                "invalid report format `{format}`, expected one of `stdout`, \
+                                 `json`, `github`, `gitlab`, or `summary`"
+                            )
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .transpose()?;
+        let dir = args
+            .value("report-dir")
+            .map_or_else(|| WORKSPACE_DIR.join("reports"), PathBuf::from);
+        Ok(Self { formats, dir })
+    }
+
+    /// Returns the selected formats, falling back to GitHub outputs when running in GitHub
+    /// Actions, and to the given default otherwise.
+    fn formats(&self, default: &[ReportFormat]) -> Vec<ReportFormat> {
+        match &self.formats {
+            Some(formats) => formats.clone(),
+            None if env::var_os("GITHUB_OUTPUT").is_some() => vec![ReportFormat::GitHub],
+            None => default.to_vec(),
+        }
+    }
+
+    pub fn matrix(&self, entries: &[MatrixEntry]) -> Result<()> {
+        for format in self.formats(&[ReportFormat::Stdout]) {
+            match format {
+                ReportFormat::Stdout => println!("{}", serde_json::to_string_pretty(entries)?),
+                ReportFormat::Json => write(
+                    self.dir.join("matrix.json"),
+                    serde_json::to_string_pretty(entries)?,
+                )?,
+                ReportFormat::GitHub => {
+                    let matrix = serde_json::json!({ "include": entries });
+                    append(
+                        github_env_file("GITHUB_OUTPUT")?,
+                        format!(
+                            "mod-list={}\nmod-count={}\n",
+                            serde_json::to_string(&matrix)?,
+                            entries.len()
+                        ),
+                    )?
+                }
+                ReportFormat::GitLab => {
+                    let matrix_file = self.dir.join("matrix.json");
+                    write(&matrix_file, serde_json::to_string_pretty(entries)?)?;
+                    write(
+                        self.dir.join("report.env"),
+                        format!(
+                            "MOD_LIST_FILE={}\nMOD_COUNT={}\n",
+                            // relative, as later jobs may check out the repo elsewhere
+                            matrix_file
+                                .strip_prefix(&*WORKSPACE_DIR)
+                                .unwrap_or(&matrix_file)
+                                .display(),
+                            entries.len()
+                        ),
+                    )?
+                }
+                ReportFormat::Summary => {
+                    let rows = entries
+                        .iter()
+                        .map(|entry| format!("| {} | {} |\n", entry.slug, entry.minecraft_version))
+                        .join("");
+                    self.summary(format!(
+                        "### Job matrix\n\n{} jobs\n\n| Mod | Minecraft |\n| --- | --- |\n{rows}\n",
+                        entries.len()
+                    ))?
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn stats(&self, stats: &Stats) -> Result<()> {
+        for format in self.formats(&[]) {
+            match format {
+                ReportFormat::Stdout => println!("{}", serde_json::to_string_pretty(stats)?),
+                ReportFormat::Json => write(
+                    self.dir.join("stats.json"),
+                    serde_json::to_string_pretty(stats)?,
+                )?,
+                ReportFormat::GitHub => append(
+                    github_env_file("GITHUB_OUTPUT")?,
+                    format!("stats<<EOF\n{}EOF\n", stats.short_markdown),
+                )?,
+                ReportFormat::GitLab => write(
+                    self.dir.join("report.env"),
+                    format!(
+                        "RULES_PARSED={}\nOUTDATED_COUNT={}\n",
+                        stats.total,
+                        stats.stale.len()
+                    ),
+                )?,
+                ReportFormat::Summary => self.summary(format!(
+                    "### Carpet rules database stats\n\n{}\n",
+                    stats.markdown
+                ))?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds to the GitHub job summary, or replaces the summary in the report dir, so repeated
+    /// local runs do not pile up.
+    fn summary(&self, content: String) -> Result<()> {
+        match env::var_os("GITHUB_STEP_SUMMARY") {
+            Some(path) => append(PathBuf::from(path), content),
+            None => write(self.dir.join("summary.md"), content),
+        }
+    }
+}
+
+fn github_env_file(var: &str) -> Result<PathBuf> {
+    env::var_os(var)
+        .map(PathBuf::from)
+        .with_context(|| format!("`{var}` must be set for GitHub reports"))
+}
+
+fn write(path: impl AsRef<Path>, content: String) -> Result<()> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content).with_context(|| format!("could not write '{}'", path.display()))
+}
+
+fn append(path: impl AsRef<Path>, content: String) -> Result<()> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    File::options()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .with_context(|| format!("could not append to '{}'", path.display()))
+}
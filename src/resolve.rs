@@ -0,0 +1,421 @@
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap},
+    fs,
+    hash::{Hash, Hasher},
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use itertools::Itertools;
+
+use crate::{
+    schema::{
+        Discover, Loader, MinecraftMajorVersion, MinecraftVersion, Mod, ModVersion, OneOrMany,
+        PrinterVersion, RunFile, VersionSource,
+    },
+    WORKSPACE_DIR,
+};
+
+/// The version of the format of the extracted data, which is part of the cache key, so that
+/// bumping it extracts all data again.
+///
+/// Bump this whenever the printers extract more or different data. Version 1 is the format from
+/// before this was introduced, which hashes to nothing.
+const FORMAT_VERSION: u32 = 3;
+
+/// The settings of one mod version with the mod-global defaults applied.
+///
+/// The hash of this is used as the cache key for the extracted data, so changing the fields or
+/// their order invalidates all existing data.
+#[derive(Hash)]
+pub struct ResolvedVersion<'a> {
+    pub mc_major: MinecraftMajorVersion,
+    pub minecraft_version: MinecraftVersion,
+    pub printer_version: &'a PrinterVersion,
+    pub entrypoint: Option<&'a String>,
+    /// the paths of the fields that store custom settings managers
+    ///
+    /// This hashes the same way the single optional path it replaced did, so data extracted with
+    /// at most one settings manager stays valid.
+    pub settings_managers: Vec<&'a String>,
+    pub settings_manager_class: &'a str,
+    pub rule_annotation_class: &'a str,
+    /// empty when discovering the rules from the settings managers without settings classes
+    pub settings_classes: &'a [String],
+    /// `None` for the default, so that it does not change the cache key of existing data
+    pub discover: IfSet<Option<Discover>>,
+    pub printer_file: IfSet<Option<SourceFile<'a>>>,
+    pub extra_mixins: IfSet<Vec<SourceFile<'a>>>,
+    pub jvm_args: IfSet<Vec<&'a String>>,
+    pub system_properties: IfSet<Option<&'a BTreeMap<String, String>>>,
+    pub gradle_properties: IfSet<Option<&'a BTreeMap<String, String>>>,
+    pub run_files: IfSet<Vec<SeededFile>>,
+    /// `None` for the default, so that it does not change the cache key of existing data
+    pub loader: IfSet<Option<Loader>>,
+    pub loader_version: Option<&'a String>,
+    pub run_client: bool,
+    /// whether to resolve the dependencies declared by the mod jar, if configured
+    pub auto_dependencies: IfSet<Option<bool>>,
+    pub dependencies: Vec<&'a String>,
+    pub source: &'a VersionSource,
+    /// `None` for version 1, see [`FORMAT_VERSION`]
+    pub format_version: IfSet<Option<u32>>,
+}
+
+impl<'a> ResolvedVersion<'a> {
+    pub fn new(
+        Mod {
+            entrypoint: default_entrypoint,
+            settings_manager: default_settings_manager,
+            settings_manager_class: default_settings_manager_class,
+            rule_annotation_class: default_rule_annotation_class,
+            settings_classes: default_settings_classes,
+            discover: default_discover,
+            printer_file: default_printer_file,
+            extra_mixins: default_extra_mixins,
+            jvm_args: default_jvm_args,
+            system_properties: default_system_properties,
+            gradle_properties: default_gradle_properties,
+            run_files: default_run_files,
+            loader: default_loader,
+            loader_version: default_loader_version,
+            run_client: default_run_client,
+            auto_dependencies: default_auto_dependencies,
+            common_dependencies,
+            ..
+        }: &'a Mod,
+        mc_major: MinecraftMajorVersion,
+        ModVersion {
+            minecraft_version,
+            printer_version,
+            entrypoint,
+            settings_manager,
+            settings_manager_class,
+            rule_annotation_class,
+            settings_classes,
+            discover,
+            printer_file,
+            extra_mixins,
+            jvm_args,
+            system_properties,
+            gradle_properties,
+            run_files,
+            loader,
+            loader_version,
+            run_client,
+            // only selects the JDK and does not affect the extracted data
+            java_version: _,
+            auto_dependencies,
+            dependencies,
+            source,
+        }: &'a ModVersion,
+    ) -> Result<Self> {
+        let entrypoint = entrypoint
+            .as_ref()
+            .or(default_entrypoint.as_ref())
+            .filter(|s| !s.is_empty());
+        let settings_managers = settings_manager
+            .as_ref()
+            .or(default_settings_manager.as_ref())
+            .map_or(&[][..], OneOrMany::as_slice)
+            .iter()
+            .filter(|s| !s.is_empty())
+            .collect_vec();
+        let settings_manager_class = settings_manager_class
+            .as_ref()
+            .or(default_settings_manager_class.as_ref())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.as_str())
+            .unwrap_or(match printer_version {
+                PrinterVersion::V1 | PrinterVersion::V2 => "carpet.settings.SettingsManager",
+                PrinterVersion::V3 => "carpet.api.settings.SettingsManager",
+                PrinterVersion::MagicLibV1 | PrinterVersion::MagicLibV2 => {
+                    "top.hendrixshen.magiclib.carpet.impl.WrappedSettingManager"
+                }
+            });
+        let rule_annotation_class = rule_annotation_class
+            .as_ref()
+            .or(default_rule_annotation_class.as_ref())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.as_str())
+            .unwrap_or(match printer_version {
+                PrinterVersion::V1 | PrinterVersion::V2 => "carpet.settings.Rule",
+                PrinterVersion::V3 => "carpet.api.settings.Rule",
+                PrinterVersion::MagicLibV1 | PrinterVersion::MagicLibV2 => {
+                    "top.hendrixshen.magiclib.carpet.api.annotation.Rule"
+                }
+            });
+        let discover = discover.or(*default_discover).unwrap_or_default();
+        let settings_classes = match (
+            settings_classes
+                .as_ref()
+                .or(default_settings_classes.as_ref()),
+            discover,
+        ) {
+            (Some(settings_classes), _) => settings_classes.as_slice(),
+            (None, Discover::Manager) => &[],
+            (None, Discover::Classes) => bail!("no settings classes specified"),
+        };
+        let discover = Some(discover).filter(|discover| *discover != Discover::default());
+        let printer_file = printer_file
+            .as_ref()
+            .or(default_printer_file.as_ref())
+            .filter(|s| !s.is_empty())
+            .map(SourceFile::read)
+            .transpose()?;
+        let extra_mixins = extra_mixins
+            .as_ref()
+            .or(default_extra_mixins.as_ref())
+            .into_iter()
+            .flatten()
+            .map(SourceFile::read)
+            .collect::<Result<Vec<_>>>()?;
+        let jvm_args = jvm_args
+            .as_ref()
+            .or(default_jvm_args.as_ref())
+            .into_iter()
+            .flatten()
+            .collect_vec();
+        let system_properties = system_properties
+            .as_ref()
+            .or(default_system_properties.as_ref())
+            .filter(|map| !map.is_empty());
+        let gradle_properties = gradle_properties
+            .as_ref()
+            .or(default_gradle_properties.as_ref())
+            .filter(|map| !map.is_empty());
+        let run_files = match run_files.as_ref().or(default_run_files.as_ref()) {
+            Some(run_files) => run_files
+                .iter()
+                .map(|(path, file)| SeededFile::read_all(path, file))
+                .flatten_ok()
+                .collect::<Result<Vec<_>>>()?,
+            None => vec![],
+        };
+        let loader = loader
+            .or(*default_loader)
+            .filter(|loader| *loader != Loader::default());
+        let loader_version = loader_version
+            .as_ref()
+            .or(default_loader_version.as_ref())
+            .filter(|s| !s.is_empty());
+        let run_client = run_client.unwrap_or(*default_run_client);
+        let auto_dependencies = auto_dependencies.or(*default_auto_dependencies);
+        let dependencies = common_dependencies.iter().chain(dependencies).collect_vec();
+
+        Ok(Self {
+            mc_major,
+            minecraft_version: *minecraft_version,
+            printer_version,
+            entrypoint,
+            settings_managers,
+            settings_manager_class,
+            rule_annotation_class,
+            settings_classes,
+            discover: IfSet(discover),
+            printer_file: IfSet(printer_file),
+            extra_mixins: IfSet(extra_mixins),
+            jvm_args: IfSet(jvm_args),
+            system_properties: IfSet(system_properties),
+            gradle_properties: IfSet(gradle_properties),
+            run_files: IfSet(run_files),
+            loader: IfSet(loader),
+            loader_version,
+            run_client,
+            auto_dependencies: IfSet(auto_dependencies),
+            dependencies,
+            source,
+            format_version: IfSet(Some(FORMAT_VERSION).filter(|version| *version != 1)),
+        })
+    }
+
+    pub fn cache_key(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// A cache key part that hashes to nothing when empty, so adding it does not change the cache
+/// key of existing data.
+pub struct IfSet<T>(pub T);
+
+impl<T: Hash> Hash for IfSet<Option<T>> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        if let Some(value) = &self.0 {
+            value.hash(state);
+        }
+    }
+}
+
+impl<T: Hash> Hash for IfSet<Vec<T>> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        if !self.0.is_empty() {
+            self.0.hash(state);
+        }
+    }
+}
+
+/// A source file from the repo, which is part of the cache key by content.
+#[derive(Hash)]
+pub struct SourceFile<'a> {
+    /// the path relative to the repo root
+    pub path: &'a String,
+    pub content: String,
+}
+
+impl<'a> SourceFile<'a> {
+    fn read(path: &'a String) -> Result<Self> {
+        let content = fs::read_to_string(WORKSPACE_DIR.join(path))
+            .with_context(|| format!("could not read '{path}'"))?;
+        Ok(Self { path, content })
+    }
+
+    /// Returns the file name without the extension, which is the name of the Java class.
+    pub fn class_name(&self) -> Result<&'a str> {
+        Path::new(self.path)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .with_context(|| format!("invalid source file path '{}'", self.path))
+    }
+}
+
+/// A file to place in the run dir, which is part of the cache key by content.
+#[derive(Hash)]
+pub struct SeededFile {
+    /// the path relative to the run dir
+    pub path: PathBuf,
+    pub content: Vec<u8>,
+}
+
+impl SeededFile {
+    /// Returns the files to place at the path, which are multiple if a directory is copied.
+    fn read_all(path: &str, file: &RunFile) -> Result<Vec<Self>> {
+        let path = Path::new(path);
+        if path.is_absolute() || path.components().any(|part| part == Component::ParentDir) {
+            bail!(
+                "run file path '{}' must be relative and inside the run dir",
+                path.display()
+            );
+        }
+        match file {
+            RunFile::Content { content } => Ok(vec![Self {
+                path: path.to_path_buf(),
+                content: content.clone().into_bytes(),
+            }]),
+            RunFile::File { file } => {
+                let source = WORKSPACE_DIR.join(file);
+                let mut files = vec![];
+                for entry in walk(&source).with_context(|| format!("could not read '{file}'"))? {
+                    files.push(Self {
+                        path: path.join(entry.strip_prefix(&source)?),
+                        content: fs::read(&entry)
+                            .with_context(|| format!("could not read '{}'", entry.display()))?,
+                    });
+                }
+                Ok(files)
+            }
+        }
+    }
+}
+
+/// Returns the path itself if it is a file, or all files inside it in a stable order.
+fn walk(path: &Path) -> Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        fs::metadata(path)?;
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files = vec![];
+    for entry in fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .sorted()
+    {
+        files.extend(walk(&entry)?);
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    fn content(content: &str) -> RunFile {
+        RunFile::Content {
+            content: content.to_owned(),
+        }
+    }
+
+    #[test]
+    fn seeded_file_rejects_paths_outside_the_run_dir() {
+        for path in [
+            "/etc/passwd",
+            "../server.properties",
+            "config/../../eula.txt",
+            "world/..",
+        ] {
+            assert!(
+                SeededFile::read_all(path, &content("")).is_err(),
+                "'{path}' was accepted"
+            );
+        }
+    }
+
+    #[test]
+    fn seeded_file_with_content() {
+        let files = SeededFile::read_all("config/./mod.json", &content("{}")).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, Path::new("config/mod.json"));
+        assert_eq!(files[0].content, b"{}");
+    }
+
+    #[test]
+    fn seeded_file_copies_directories_in_a_stable_order() {
+        let source = env::temp_dir().join(format!("carpet-database-seed-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&source);
+        for (path, content) in [
+            ("level.dat", "level"),
+            ("region/r.0.0.mca", "region 0"),
+            ("datapacks/pack/pack.mcmeta", "pack"),
+            ("region/r.-1.0.mca", "region -1"),
+        ] {
+            let path = source.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+
+        let files = SeededFile::read_all(
+            "world",
+            &RunFile::File {
+                file: source.to_string_lossy().into_owned(),
+            },
+        );
+        fs::remove_dir_all(&source).unwrap();
+        let files = files
+            .unwrap()
+            .into_iter()
+            .map(|file| (file.path, String::from_utf8(file.content).unwrap()))
+            .collect_vec();
+        assert_eq!(
+            files,
+            [
+                ("world/datapacks/pack/pack.mcmeta", "pack"),
+                ("world/level.dat", "level"),
+                ("world/region/r.-1.0.mca", "region -1"),
+                ("world/region/r.0.0.mca", "region 0"),
+            ]
+            .map(|(path, content)| (PathBuf::from(path), content.to_owned()))
+        );
+    }
+
+    #[test]
+    fn seeded_file_rejects_missing_files() {
+        let file = RunFile::File {
+            file: "does/not/exist.json".to_owned(),
+        };
+        assert!(SeededFile::read_all("config/mod.json", &file).is_err());
+    }
+}
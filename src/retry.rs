@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use reqwest::StatusCode;
+
+use crate::{log, CLIENT};
+
+/// How often a request is attempted before giving up.
+const MAX_HTTP_ATTEMPTS: u32 = 5;
+const INITIAL_DELAY: Duration = Duration::from_secs(2);
+const MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// Returns the delay before the given retry, doubling with each attempt.
+pub fn backoff(attempt: u32) -> Duration {
+    INITIAL_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(MAX_DELAY)
+}
+
+/// Returns whether a request with this response status might succeed when sent again.
+fn is_transient(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+/// Sends a GET request and reads the response body.
+///
+/// Connection errors and transient status codes are retried with exponential backoff. The final
+/// status code is returned along with the body, so callers can report unsuccessful responses.
+pub async fn get(url: &str) -> Result<(StatusCode, Vec<u8>)> {
+    let mut attempt = 1;
+    loop {
+        log::trace!("GET {url}");
+        let result = async {
+            let res = CLIENT.get(url).send().await?;
+            let status = res.status();
+            Ok::<_, reqwest::Error>((status, res.bytes().await?.to_vec()))
+        }
+        .await;
+        let reason = match result {
+            Ok((status, _)) if is_transient(status) && attempt < MAX_HTTP_ATTEMPTS => {
+                format!("status code {status}")
+            }
+            Err(err) if !err.is_builder() && attempt < MAX_HTTP_ATTEMPTS => err.to_string(),
+            result => return Ok(result?),
+        };
+        let delay = backoff(attempt);
+        log::warning!(
+            "request to '{url}' failed with {reason}, retrying in {}s ({attempt}/{})",
+            delay.as_secs(),
+            MAX_HTTP_ATTEMPTS - 1,
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
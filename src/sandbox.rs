@@ -0,0 +1,126 @@
+use std::{env, path::PathBuf, process::Command};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{cli::Args, log, process, ACTIVE_DIR};
+
+/// Runs extractions without network access and with a read-only file system, except for the
+/// active mod dir. The Gradle user home is writable through a throwaway overlay, so nothing the
+/// mod does there persists.
+pub struct Sandbox {
+    /// the maximum memory of all processes, in the format of systemd's `MemoryMax`, like `4G`
+    memory: Option<String>,
+    /// the maximum number of CPUs to use, like `2` or `0.5`
+    cpus: Option<f64>,
+    /// whether to fall back to `unshare`, which only disables the network, without `bwrap`
+    network_only: bool,
+}
+
+impl Sandbox {
+    /// Returns the sandbox configured with `--sandbox`, `--sandbox-memory`, `--sandbox-cpus`, and
+    /// `--sandbox-network-only`.
+    pub fn from_args(args: &Args) -> Result<Option<Self>> {
+        let memory = args.value("sandbox-memory").map(str::to_owned);
+        let cpus = args.parse_value::<f64>("sandbox-cpus")?;
+        let network_only = args.flag("sandbox-network-only");
+        if !args.flag("sandbox") {
+            if memory.is_some() || cpus.is_some() || network_only {
+                bail!(
+                    "`--sandbox-memory`, `--sandbox-cpus`, and `--sandbox-network-only` require \
+                     `--sandbox`"
+                );
+            }
+            return Ok(None);
+        }
+        if !cfg!(target_os = "linux") {
+            bail!("`--sandbox` is only supported on Linux");
+        }
+        Ok(Some(Self {
+            memory,
+            cpus,
+            network_only,
+        }))
+    }
+
+    /// Wraps the command, so that it runs inside the sandbox.
+    ///
+    /// The program, arguments, environment, and working dir are kept.
+    pub fn wrap(&self, cmd: Command) -> Result<Command> {
+        let mut sandboxed = if let Some(bwrap) = process::find_executable("bwrap") {
+            let gradle_home = env::var_os("GRADLE_USER_HOME")
+                .map(PathBuf::from)
+                .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".gradle")))
+                .context("could not determine the Gradle user home")?;
+            let mut bwrap = Command::new(bwrap);
+            bwrap
+                .args(["--ro-bind", "/", "/"])
+                .args(["--dev", "/dev"])
+                .args(["--proc", "/proc"])
+                .args(["--tmpfs", "/tmp"])
+                .arg("--bind")
+                .args([&*ACTIVE_DIR, &*ACTIVE_DIR])
+                // requires bubblewrap 0.8 or newer
+                .arg("--overlay-src")
+                .arg(&gradle_home)
+                .arg("--tmp-overlay")
+                .arg(&gradle_home)
+                .args([
+                    "--unshare-net",
+                    "--unshare-ipc",
+                    "--unshare-uts",
+                    "--unshare-pid",
+                ])
+                .arg("--die-with-parent");
+            if let Some(dir) = cmd.get_current_dir() {
+                bwrap.arg("--chdir").arg(dir);
+            }
+            bwrap.arg("--");
+            bwrap
+        } else if !self.network_only {
+            bail!(
+                "sandboxing requires `bwrap` (bubblewrap), but it was not found. Pass \
+                 `--sandbox-network-only` to only disable the network with `unshare` instead"
+            );
+        } else if let Some(unshare) = process::find_executable("unshare") {
+            log::warning!(
+                "`bwrap` was not found, falling back to `unshare` which only disables the network"
+            );
+            let mut unshare = Command::new(unshare);
+            unshare.args(["--net", "--map-root-user", "--"]);
+            unshare
+        } else {
+            bail!("sandboxing requires `bwrap` (bubblewrap) or `unshare`, but neither was found");
+        };
+        sandboxed.arg(cmd.get_program()).args(cmd.get_args());
+
+        // limits are enforced with a transient cgroup, which contains all descendants
+        if self.memory.is_some() || self.cpus.is_some() {
+            let systemd_run = process::find_executable("systemd-run")
+                .context("resource limits require `systemd-run`, but it was not found")?;
+            let mut limited = Command::new(systemd_run);
+            limited.args(["--user", "--scope", "--quiet"]);
+            if let Some(memory) = &self.memory {
+                limited.arg(format!("--property=MemoryMax={memory}"));
+            }
+            if let Some(cpus) = self.cpus {
+                limited.arg(format!("--property=CPUQuota={}%", (cpus * 100.0).round()));
+            }
+            limited
+                .arg("--")
+                .arg(sandboxed.get_program())
+                .args(sandboxed.get_args());
+            sandboxed = limited;
+        }
+
+        if let Some(dir) = cmd.get_current_dir() {
+            sandboxed.current_dir(dir);
+        }
+        for (key, value) in cmd.get_envs() {
+            match value {
+                Some(value) => sandboxed.env(key, value),
+                None => sandboxed.env_remove(key),
+            };
+        }
+        Ok(sandboxed)
+    }
+}
@@ -3,6 +3,7 @@ use std::collections::BTreeMap;
 use serde::{Deserialize, Serialize};
 
 pub type CombinedJson = Vec<Rule>;
+pub type CombinedLoggersJson = Vec<Logger>;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct Rule {
@@ -19,6 +20,8 @@ pub struct Rule {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub validators: Vec<String>,
     pub config_files: Vec<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub translations: BTreeMap<String, RuleTranslation>,
     pub mod_name: String,
     pub mod_slug: String,
     pub mod_url: String,
@@ -26,10 +29,48 @@ pub struct Rule {
     pub version_urls: Vec<String>,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct Logger {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    pub options: Vec<String>,
+    pub hud: bool,
+    pub mod_name: String,
+    pub mod_slug: String,
+    pub mod_url: String,
+    pub minecraft_versions: Vec<MinecraftMajorVersion>,
+    pub version_urls: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CombineReport {
+    pub stale: Vec<StaleEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StaleEntry {
+    pub mod_slug: String,
+    pub minecraft_version: MinecraftMajorVersion,
+    pub decision: StaleDecision,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StaleDecision {
+    Fallback,
+    Dropped,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RulesJson {
     pub hash: u64,
+    /// the dependencies that were resolved automatically for the extraction
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<String>,
     pub rules: Vec<RawRule>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub loggers: Vec<RawLogger>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,6 +86,33 @@ pub struct RawRule {
     pub extras: Vec<String>,
     pub validators: Vec<String>,
     pub config_files: Vec<String>,
+    /// the translations by language code, like `zh_cn`, without English
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub translations: BTreeMap<String, RuleTranslation>,
+}
+
+/// A `/log` logger registered in Carpet's `LoggerRegistry`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawLogger {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    pub options: Vec<String>,
+    /// whether this is a HUD logger, which shows in the tab list
+    pub hud: bool,
+    /// whether Carpet registered the logger itself, which is only needed to attribute it
+    #[serde(default, skip_serializing)]
+    pub carpet: bool,
+}
+
+/// The translated texts of a rule in one language.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleTranslation {
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extras: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub additional: Option<String>,
 }
 
 #[derive(Debug, Clone, Hash, Deserialize)]
@@ -65,37 +133,132 @@ pub struct Mod {
     #[serde(default)]
     pub repo: String,
     pub entrypoint: Option<String>,
-    pub settings_manager: Option<String>,
+    pub settings_manager: Option<OneOrMany<String>>,
     pub settings_manager_class: Option<String>,
     pub rule_annotation_class: Option<String>,
     pub settings_classes: Option<Vec<String>>,
+    pub discover: Option<Discover>,
+    /// a custom printer source file relative to the repo root
+    pub printer_file: Option<String>,
+    /// mixin source files relative to the repo root, which are added to the printer mod
+    pub extra_mixins: Option<Vec<String>>,
+    /// extra arguments for the Minecraft JVM
+    pub jvm_args: Option<Vec<String>>,
+    /// system properties for the Minecraft JVM
+    pub system_properties: Option<BTreeMap<String, String>>,
+    /// entries to set in the template's `gradle.properties`
+    pub gradle_properties: Option<BTreeMap<String, String>>,
+    /// files to place in the run dir before extracting, by their path relative to it
+    pub run_files: Option<BTreeMap<String, RunFile>>,
+    pub loader: Option<Loader>,
     // optional manual override of the Fabric loader version
     pub loader_version: Option<String>,
     #[serde(default)]
     pub run_client: bool,
+    pub auto_dependencies: Option<bool>,
     // dependencies that are common across all versions
     #[serde(default)]
     pub common_dependencies: Vec<String>,
     pub versions: BTreeMap<MinecraftMajorVersion, ModVersion>,
 }
 
+impl Mod {
+    /// Returns the loader of the mod version, see [`ModVersion::loader`].
+    pub fn loader(&self, version: &ModVersion) -> Loader {
+        version.loader.or(self.loader).unwrap_or_default()
+    }
+
+    /// Returns the name of the template mod dir for the mod version.
+    pub fn template_name(&self, version: &ModVersion) -> String {
+        self.loader(version)
+            .template_name(version.minecraft_version)
+    }
+}
+
 #[derive(Debug, Clone, Hash, Deserialize)]
 pub struct ModVersion {
     pub minecraft_version: MinecraftVersion,
     pub printer_version: PrinterVersion,
     pub entrypoint: Option<String>,
-    pub settings_manager: Option<String>,
+    pub settings_manager: Option<OneOrMany<String>>,
     pub settings_manager_class: Option<String>,
     pub rule_annotation_class: Option<String>,
     pub settings_classes: Option<Vec<String>>,
+    pub discover: Option<Discover>,
+    /// a custom printer source file relative to the repo root
+    pub printer_file: Option<String>,
+    /// mixin source files relative to the repo root, which are added to the printer mod
+    pub extra_mixins: Option<Vec<String>>,
+    /// extra arguments for the Minecraft JVM
+    pub jvm_args: Option<Vec<String>>,
+    /// system properties for the Minecraft JVM
+    pub system_properties: Option<BTreeMap<String, String>>,
+    /// entries to set in the template's `gradle.properties`
+    pub gradle_properties: Option<BTreeMap<String, String>>,
+    /// files to place in the run dir before extracting, by their path relative to it
+    pub run_files: Option<BTreeMap<String, RunFile>>,
+    pub loader: Option<Loader>,
     pub loader_version: Option<String>,
     pub run_client: Option<bool>,
-    /// dependencies other than Fabric API
+    /// optional exact Java version, instead of the minimum from [`MinecraftVersion::java_version`]
+    pub java_version: Option<u32>,
+    /// whether to resolve the Carpet and library dependencies declared by the mod jar
+    pub auto_dependencies: Option<bool>,
+    /// dependencies other than Fabric API, which override resolved ones for the same project
     #[serde(default)]
     pub dependencies: Vec<String>,
     pub source: VersionSource,
 }
 
+/// The mod loader the template mod is built for.
+#[derive(
+    Debug, Clone, Copy, Default, Hash, PartialEq, Eq, PartialOrd, Ord, Deserialize, strum::Display,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Loader {
+    #[default]
+    Fabric,
+    Quilt,
+}
+
+impl Loader {
+    /// Returns the name of the template mod dir for the Minecraft version.
+    pub fn template_name(self, minecraft_version: MinecraftVersion) -> String {
+        match self {
+            Loader::Fabric => minecraft_version.to_string(),
+            Loader::Quilt => format!("{minecraft_version}-quilt"),
+        }
+    }
+}
+
+/// A file to place in the run dir.
+#[derive(Debug, Clone, Hash, Deserialize)]
+#[serde(untagged)]
+pub enum RunFile {
+    /// the content of the file
+    Content { content: String },
+    /// a file or directory relative to the repo root, which is copied
+    File { file: String },
+}
+
+/// A single value or a list of values.
+#[derive(Debug, Clone, Hash, Deserialize)]
+#[serde(untagged)]
+pub enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+impl<T> OneOrMany<T> {
+    pub fn as_slice(&self) -> &[T] {
+        match self {
+            OneOrMany::One(value) => std::slice::from_ref(value),
+            OneOrMany::Many(values) => values,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Hash, Deserialize)]
 #[serde(tag = "host")]
 pub enum VersionSource {
@@ -127,9 +290,21 @@ pub enum PrinterVersion {
     MagicLibV2,
 }
 
+/// How the printer finds the rules to extract.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq, Deserialize, strum::Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Discover {
+    /// the fields annotated as rules in the settings classes
+    #[default]
+    Classes,
+    /// all rules registered in the settings managers, except for the ones of Carpet itself
+    Manager,
+}
+
 macro_rules! mc_version_enum {
     ($name:ident; $($variant:ident = $str:literal),+ $(,)?) => {
-        #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, strum::Display, strum::AsRefStr)]
+        #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, strum::Display, strum::AsRefStr, strum::EnumString)]
         pub enum $name {$(
             #[serde(rename = $str)]
             #[strum(serialize = $str)]
@@ -177,6 +352,23 @@ mc_version_enum! {
     V1_20_2 = "1.20.2",
     V1_20_3 = "1.20.3",
     V1_20_4 = "1.20.4",
+    V1_20_5 = "1.20.5",
+    V1_20_6 = "1.20.6",
+}
+
+impl MinecraftVersion {
+    /// The minimum major Java version required to run this Minecraft version.
+    pub fn java_version(self) -> u32 {
+        if self >= Self::V1_20_5 {
+            21
+        } else if self >= Self::V1_18_1 {
+            17
+        } else if self >= Self::V1_17_1 {
+            16
+        } else {
+            8
+        }
+    }
 }
 
 impl From<MinecraftVersion> for MinecraftMajorVersion {
@@ -207,6 +399,8 @@ impl From<MinecraftVersion> for MinecraftMajorVersion {
             MinecraftVersion::V1_20_2 => MinecraftMajorVersion::V1_20,
             MinecraftVersion::V1_20_3 => MinecraftMajorVersion::V1_20,
             MinecraftVersion::V1_20_4 => MinecraftMajorVersion::V1_20,
+            MinecraftVersion::V1_20_5 => MinecraftMajorVersion::V1_20,
+            MinecraftVersion::V1_20_6 => MinecraftMajorVersion::V1_20,
         }
     }
 }
@@ -251,6 +445,8 @@ impl MinecraftMajorVersion {
                 MinecraftVersion::V1_20_2,
                 MinecraftVersion::V1_20_3,
                 MinecraftVersion::V1_20_4,
+                MinecraftVersion::V1_20_5,
+                MinecraftVersion::V1_20_6,
             ],
         }
     }
@@ -0,0 +1,72 @@
+use anyhow::Result;
+use xshell::Shell;
+
+use crate::{
+    log::{self, Level},
+    read_data,
+    resolve::{IfSet, ResolvedVersion},
+    schema::Mod,
+};
+
+/// Prints the state of the data of every mod version and the dependencies used to extract it.
+///
+/// This does not access the network, so automatically resolved dependencies are only known for
+/// up-to-date data.
+pub fn status(sh: &Shell, mods: &[Mod]) -> Result<()> {
+    log::header!("status");
+    for mod_ in mods {
+        for (mc_major, version) in &mod_.versions {
+            let resolved = match ResolvedVersion::new(mod_, *mc_major, version) {
+                Ok(resolved) => resolved,
+                Err(err) => {
+                    log::event(
+                        Level::Info,
+                        "1",
+                        "",
+                        format_args!(
+                            "{} {mc_major}: {}",
+                            mod_.slug,
+                            log::paint("31", format_args!("invalid: {err:#}"))
+                        ),
+                    );
+                    continue;
+                }
+            };
+            let data = read_data(sh, &mod_.slug, mc_major);
+            let up_to_date = data
+                .as_ref()
+                .filter(|data| data.hash == resolved.cache_key());
+            let (color, state) = match (&data, up_to_date) {
+                (_, Some(_)) => ("32", "up-to-date"),
+                (Some(_), None) => ("33", "outdated"),
+                (None, _) => ("31", "missing"),
+            };
+            log::event(
+                Level::Info,
+                "1",
+                "",
+                format_args!("{} {mc_major}: {}", mod_.slug, log::paint(color, state)),
+            );
+
+            for dep in &resolved.dependencies {
+                log::event(Level::Info, "", "  ", format_args!("{dep} (explicit)"));
+            }
+            let IfSet(auto_dependencies) = resolved.auto_dependencies;
+            match up_to_date {
+                _ if !auto_dependencies.unwrap_or(false) => {}
+                Some(data) => {
+                    for dep in &data.dependencies {
+                        log::event(Level::Info, "", "  ", format_args!("{dep} (automatic)"));
+                    }
+                }
+                None => log::event(
+                    Level::Info,
+                    "2",
+                    "  ",
+                    format_args!("automatic dependencies are resolved on the next extraction"),
+                ),
+            }
+        }
+    }
+    Ok(())
+}
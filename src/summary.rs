@@ -0,0 +1,139 @@
+use crate::{
+    log::{self, Level},
+    schema::{MinecraftMajorVersion, StaleDecision, StaleEntry},
+};
+
+/// The outcome of getting the data for one mod version.
+pub enum Status {
+    /// data was extracted and saved
+    Succeeded,
+    /// data was already up-to-date
+    Skipped,
+    /// data was outdated, the last good data was used instead
+    FellBack,
+    /// data was outdated or missing and left out
+    Dropped,
+    Failed(anyhow::Error),
+}
+
+/// Collects the outcome of every mod version of a run.
+#[derive(Default)]
+pub struct Summary {
+    entries: Vec<(String, MinecraftMajorVersion, Status)>,
+}
+
+impl Summary {
+    pub fn record(&mut self, slug: &str, mc_major: MinecraftMajorVersion, status: Status) {
+        self.entries.push((slug.to_owned(), mc_major, status));
+    }
+
+    pub fn failure_count(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|(_, _, status)| matches!(status, Status::Failed(_)))
+            .count()
+    }
+
+    pub fn has_failures(&self) -> bool {
+        self.failure_count() > 0
+    }
+
+    /// Returns all mod versions for which outdated data was used or dropped.
+    pub fn stale_entries(&self) -> Vec<StaleEntry> {
+        self.entries
+            .iter()
+            .filter_map(|(slug, mc_major, status)| {
+                let decision = match status {
+                    Status::FellBack => StaleDecision::Fallback,
+                    Status::Dropped => StaleDecision::Dropped,
+                    _ => return None,
+                };
+                Some(StaleEntry {
+                    mod_slug: slug.clone(),
+                    minecraft_version: *mc_major,
+                    decision,
+                })
+            })
+            .collect()
+    }
+
+    /// Prints a table of all mod versions and their status, followed by the error chain of every
+    /// failure.
+    pub fn print(&self) {
+        if self.entries.is_empty() {
+            return;
+        }
+
+        let slug_width = self
+            .entries
+            .iter()
+            .map(|(slug, _, _)| slug.len())
+            .max()
+            .unwrap_or_default()
+            .max("mod".len());
+        let (mut succeeded, mut skipped, mut stale, mut failed) = (0, 0, 0, 0);
+        log::header!("summary");
+        log::event(
+            Level::Info,
+            "1",
+            "",
+            format_args!("{:slug_width$}  version  status", "mod"),
+        );
+        for (slug, mc_major, status) in &self.entries {
+            let (color, status) = match status {
+                Status::Succeeded => {
+                    succeeded += 1;
+                    ("32", "succeeded")
+                }
+                Status::Skipped => {
+                    skipped += 1;
+                    ("34", "skipped")
+                }
+                Status::FellBack => {
+                    stale += 1;
+                    ("33", "fell back")
+                }
+                Status::Dropped => {
+                    stale += 1;
+                    ("33", "dropped")
+                }
+                Status::Failed(_) => {
+                    failed += 1;
+                    ("31", "failed")
+                }
+            };
+            log::event(
+                Level::Info,
+                "",
+                "",
+                format_args!(
+                    "{slug:slug_width$}  {:7}  {}",
+                    mc_major.as_ref(),
+                    log::paint(color, status)
+                ),
+            );
+        }
+        log::event(
+            Level::Info,
+            "1",
+            "",
+            format_args!(
+                "{succeeded} succeeded, {skipped} skipped, {stale} outdated, {failed} failed"
+            ),
+        );
+
+        for (slug, mc_major, status) in &self.entries {
+            let Status::Failed(err) = status else {
+                continue;
+            };
+            log::error!(">> {slug} for Minecraft {mc_major}:");
+            for (index, cause) in err.chain().enumerate() {
+                if index == 0 {
+                    log::event(Level::Error, "31", "", format_args!("{cause}"));
+                } else {
+                    log::event(Level::Error, "31", "  caused by: ", format_args!("{cause}"));
+                }
+            }
+        }
+    }
+}
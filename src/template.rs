@@ -0,0 +1,135 @@
+//! Filling `${NAME}` placeholders in the printer sources and other generated files.
+
+use std::collections::BTreeSet;
+
+use anyhow::{bail, Result};
+use itertools::Itertools;
+use lazy_regex::{regex, Captures};
+
+/// Replaces every `${NAME}` placeholder in the template with its value.
+///
+/// A literal `${` is written as `$${`. Placeholders without a value and values without a
+/// placeholder are errors, so templates and callers cannot silently drift apart.
+pub fn render(name: &str, template: &str, values: &[(&str, &str)]) -> Result<String> {
+    render_impl(name, template, values, true)
+}
+
+/// Like [`render`], but the template may leave values unused.
+///
+/// This is for custom printers from `mods.toml`. They are written for a single mod, which might
+/// have no settings classes or discover the rules in its own way, so they cannot be required to
+/// reference every value the built-in printers need. The values are still the complete list of
+/// allowed placeholders, so misspelled placeholders are rejected as unknown.
+pub fn render_subset(name: &str, template: &str, values: &[(&str, &str)]) -> Result<String> {
+    render_impl(name, template, values, false)
+}
+
+fn render_impl(
+    name: &str,
+    template: &str,
+    values: &[(&str, &str)],
+    require_all: bool,
+) -> Result<String> {
+    let mut unknown = BTreeSet::new();
+    let mut used = BTreeSet::new();
+    let rendered = regex!(r"(\$?)\$\{([^}]*)\}")
+        .replace_all(template, |caps: &Captures| {
+            // an escaped placeholder is kept without the escaping `$`
+            if !caps[1].is_empty() {
+                return caps[0][1..].to_owned();
+            }
+            let placeholder = &caps[2];
+            match values.iter().find(|(key, _)| *key == placeholder) {
+                Some((key, value)) => {
+                    used.insert(*key);
+                    value.to_string()
+                }
+                None => {
+                    unknown.insert(placeholder.to_owned());
+                    String::new()
+                }
+            }
+        })
+        .into_owned();
+
+    if !unknown.is_empty() {
+        bail!(
+            "template {name} contains unknown placeholders: {}",
+            unknown.iter().map(|key| format!("${{{key}}}")).join(", ")
+        );
+    }
+    let unused = values
+        .iter()
+        .map(|(key, _)| *key)
+        .filter(|key| !used.contains(key))
+        .collect_vec();
+    if require_all && !unused.is_empty() {
+        bail!(
+            "template {name} does not use the placeholders: {}",
+            unused.iter().map(|key| format!("${{{key}}}")).join(", ")
+        );
+    }
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALUES: &[(&str, &str)] = &[("MANAGERS", "a, b"), ("RULE", "carpet.api.settings.Rule")];
+
+    #[test]
+    fn render_fills_placeholders() {
+        assert_eq!(
+            render("test", "${MANAGERS} ${RULE} ${RULE}", VALUES).unwrap(),
+            "a, b carpet.api.settings.Rule carpet.api.settings.Rule"
+        );
+    }
+
+    #[test]
+    fn render_keeps_escaped_placeholders() {
+        assert_eq!(
+            render("test", "$${MANAGERS} ${MANAGERS} $${OTHER} ${RULE}", VALUES).unwrap(),
+            "${MANAGERS} a, b ${OTHER} carpet.api.settings.Rule"
+        );
+    }
+
+    #[test]
+    fn render_rejects_unknown_placeholders() {
+        let err = render("test", "${MANAGERS} ${RULE} ${RULES} ${}", VALUES).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "template test contains unknown placeholders: ${}, ${RULES}"
+        );
+    }
+
+    #[test]
+    fn render_rejects_unused_values() {
+        let err = render("test", "${MANAGERS} $${RULE}", VALUES).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "template test does not use the placeholders: ${RULE}"
+        );
+    }
+
+    #[test]
+    fn render_subset_allows_unused_values() {
+        assert_eq!(
+            render_subset("test", "${RULE}", VALUES).unwrap(),
+            "carpet.api.settings.Rule"
+        );
+        assert_eq!(
+            render_subset("test", "no placeholders", VALUES).unwrap(),
+            "no placeholders"
+        );
+    }
+
+    #[test]
+    fn render_subset_rejects_unknown_placeholders() {
+        let err = render_subset("test", "${RULE} ${SETTINGS}", VALUES).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "template test contains unknown placeholders: ${SETTINGS}"
+        );
+    }
+}
@@ -1,14 +1,15 @@
 use std::collections::{hash_map::Entry, HashMap};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use chrono::{DateTime, Utc};
 use ferinth::{structures::version::Version, Ferinth};
 use once_cell::sync::Lazy;
 use serde::Deserialize;
 
 use crate::{
+    log::{self, Level},
+    retry,
     schema::{MinecraftMajorVersion, Mod, VersionSource},
-    CLIENT,
 };
 
 static FERINTH: Lazy<Ferinth> = Lazy::new(Ferinth::default);
@@ -33,7 +34,7 @@ pub async fn search_updates(mods: &[Mod]) -> Result<()> {
                     .await?
                 }
                 VersionSource::GitHub { .. } => {
-                    println!("\x1b[1;30mskipping GitHub source for {}\x1b[0m", &mod_.slug)
+                    log::debug!("skipping GitHub source for {}", &mod_.slug)
                 }
             }
         }
@@ -66,18 +67,23 @@ async fn search_modrinth(
             .find(|v| v.game_versions.iter().any(|s| s == minor))
         {
             if latest.version_number == current_version || latest.id == current_version {
-                println!("\x1b[1;30m{slug} on {mc_major} is up to date\x1b[0m");
+                log::debug!("{slug} on {mc_major} is up to date");
             } else {
-                println!(
-                    "\x1b[1;32m{slug} has new version for {mc_major}: name '{}', id '{}'\x1b[0m",
-                    latest.version_number, latest.id
+                log::event(
+                    Level::Info,
+                    "1;32",
+                    "",
+                    format_args!(
+                        "{slug} has new version for {mc_major}: name '{}', id '{}'",
+                        latest.version_number, latest.id
+                    ),
                 );
             }
             return Ok(());
         }
     }
 
-    eprintln!("\x1b[1;31mERROR: no version found for {slug} on {mc_major}\x1b[0m");
+    log::error!("no version found for {slug} on {mc_major}");
     dbg!(versions);
 
     Ok(())
@@ -104,14 +110,14 @@ async fn search_curseforge(
 ) -> Result<()> {
     let project = match cache.entry(project_id) {
         Entry::Vacant(entry) => {
-            let mut project: CFProject = serde_json::from_str(
-                &CLIENT
-                    .get(format!("https://api.cfwidget.com/{project_id}"))
-                    .send()
-                    .await?
-                    .text()
-                    .await?,
-            )?;
+            let (status, body) =
+                retry::get(&format!("https://api.cfwidget.com/{project_id}")).await?;
+            if !status.is_success() {
+                bail!(
+                    "could not get CurseForge files: cfwidget responded with status code {status}"
+                );
+            }
+            let mut project: CFProject = serde_json::from_slice(&body)?;
             project.files.sort_by_key(|f| f.uploaded_at);
             entry.insert(project)
         }
@@ -123,18 +129,23 @@ async fn search_curseforge(
             f.versions.iter().any(|s| s == "Fabric") && f.versions.iter().any(|s| s == minor)
         }) {
             if latest.id == file_id {
-                println!("\x1b[1;30m{slug} on {mc_major} is up to date on CurseForge\x1b[0m");
+                log::debug!("{slug} on {mc_major} is up to date on CurseForge");
             } else {
-                println!(
-                    "\x1b[1;33m{slug} has new version for {mc_major} on CurseForge: id '{}'\x1b[0m",
-                    latest.id
+                log::event(
+                    Level::Info,
+                    "1;33",
+                    "",
+                    format_args!(
+                        "{slug} has new version for {mc_major} on CurseForge: id '{}'",
+                        latest.id
+                    ),
                 );
             }
             return Ok(());
         }
     }
 
-    eprintln!("\x1b[1;31mERROR: no version found for {slug} on {mc_major}\x1b[0m");
+    log::error!("no version found for {slug} on {mc_major}");
     dbg!(project);
 
     Ok(())